@@ -0,0 +1,54 @@
+use acdc::{attributes::InlineAttributes, template::AttestationTemplate, Attestation, Attributes};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use said::{
+    derivation::{HashFunction, HashFunctionCode},
+    sad::{SerializationFormats, SAD},
+};
+
+fn attributes() -> Attributes {
+    let mut attributes = InlineAttributes::default();
+    attributes.insert("name".to_string(), "Hella".into());
+    attributes.insert("dt".to_string(), "2023-06-01T12:00:00+00:00".into());
+    attributes.insert("score".to_string(), 42.into());
+    attributes.to_untargeted_public_block(&SerializationFormats::JSON)
+}
+
+fn bench_issuance(c: &mut Criterion) {
+    let code = HashFunctionCode::Blake3_256;
+    let hash = HashFunction::from(code.clone());
+    let schema = hash.derive(b"schema").to_string();
+    let registry = Some(hash.derive(b"registry"));
+
+    c.bench_function("compute_digest", |b| {
+        b.iter_batched(
+            attributes,
+            |attrs| {
+                let mut acdc = Attestation {
+                    digest: None,
+                    issuer: "issuer".to_string(),
+                    registry_identifier: registry.clone(),
+                    schema: schema.clone(),
+                    attrs,
+                    edges: None,
+                    rules: None,
+                };
+                acdc.compute_digest(&code, &SerializationFormats::JSON);
+                acdc
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    let template =
+        AttestationTemplate::new("issuer", registry.clone(), schema.clone(), &code).unwrap();
+    c.bench_function("template_issue", |b| {
+        b.iter_batched(
+            attributes,
+            |attrs| template.issue(attrs).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(benches, bench_issuance);
+criterion_main!(benches);
@@ -0,0 +1,143 @@
+//! Aggregated attributes for selective disclosure.
+//!
+//! Every attribute is blinded separately with its own salt, and the aggregate
+//! SAID commits to the list of blinded digests. Holder can then reveal any
+//! subset of attributes, which verifier checks against the aggregate without
+//! learning the others. See: [`AggregatedAttributes`]
+
+use indexmap::IndexMap;
+use said::{
+    derivation::{HashFunction, HashFunctionCode},
+    sad::{SerializationFormats, SAD},
+    SelfAddressingIdentifier,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    attributes::{InlineAttributes, SaidBlock},
+    salt::new_uuid,
+};
+
+/// Attribute revealed to verifier: its key, value and salt.
+pub type Disclosure = (String, Value, String);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggregatedAttributes {
+    /// Aggregate SAID over blinded attribute digests.
+    #[serde(rename = "d")]
+    pub said: SelfAddressingIdentifier,
+
+    /// Blinded digests of individual attributes, in insertion order.
+    #[serde(rename = "a")]
+    pub digests: Vec<SelfAddressingIdentifier>,
+}
+
+impl AggregatedAttributes {
+    /// Blind every attribute with a fresh salt and aggregate the digests.
+    /// Returned disclosures are kept by the holder to reveal attributes later.
+    pub fn new(
+        attributes: IndexMap<String, Value>,
+        code: &HashFunctionCode,
+    ) -> (Self, Vec<Disclosure>) {
+        let disclosures: Vec<Disclosure> = attributes
+            .into_iter()
+            .map(|(key, value)| (key, value, new_uuid()))
+            .collect();
+        let digests = disclosures
+            .iter()
+            .map(|(key, value, salt)| blinded_digest(key, value, salt, code))
+            .collect();
+        (Self::from_digests(digests, code), disclosures)
+    }
+
+    fn from_digests(digests: Vec<SelfAddressingIdentifier>, code: &HashFunctionCode) -> Self {
+        Self {
+            said: aggregate(&digests, code),
+            digests,
+        }
+    }
+
+    /// Check that aggregate SAID commits to blinded digests and that every
+    /// disclosed attribute hashes into one of them.
+    pub fn verify_disclosed(&self, disclosed: &[Disclosure]) -> bool {
+        let code = self.said.derivation.clone().into();
+        aggregate(&self.digests, &code) == self.said
+            && disclosed.iter().all(|(key, value, salt)| {
+                self.digests
+                    .contains(&blinded_digest(key, value, salt, &code))
+            })
+    }
+}
+
+/// SAID of `{"d": "...", "u": salt, "k": key, "v": value}` block. Key and
+/// value go in fields of their own, so attribute named `d` or `u` can't
+/// clash with SAID or salt.
+fn blinded_digest(
+    key: &str,
+    value: &Value,
+    salt: &str,
+    code: &HashFunctionCode,
+) -> SelfAddressingIdentifier {
+    let mut data = InlineAttributes::default();
+    data.insert("u".to_string(), salt.into());
+    data.insert("k".to_string(), key.into());
+    data.insert("v".to_string(), value.clone());
+    let mut block = SaidBlock { said: None, data };
+    block.compute_digest(code, &SerializationFormats::JSON);
+    block.said.unwrap()
+}
+
+/// Digest of concatenated text representations of `digests`.
+fn aggregate(
+    digests: &[SelfAddressingIdentifier],
+    code: &HashFunctionCode,
+) -> SelfAddressingIdentifier {
+    let concatenated: String = digests.iter().map(ToString::to_string).collect();
+    HashFunction::from(code.clone()).derive(concatenated.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+    use said::derivation::HashFunctionCode;
+
+    use super::AggregatedAttributes;
+
+    #[test]
+    pub fn test_partial_disclosure() {
+        let mut attributes = IndexMap::new();
+        attributes.insert("name".to_string(), "Hella".into());
+        attributes.insert("age".to_string(), 42.into());
+        attributes.insert("city".to_string(), "Geneva".into());
+        let (aggregated, disclosures) =
+            AggregatedAttributes::new(attributes, &HashFunctionCode::Blake3_256);
+        assert_eq!(aggregated.digests.len(), 3);
+
+        // Reveal 1 of 3 and 2 of 3 attributes.
+        assert!(aggregated.verify_disclosed(&disclosures[1..2]));
+        assert!(aggregated.verify_disclosed(&[disclosures[0].clone(), disclosures[2].clone()]));
+
+        // Changed value or key doesn't hash into the aggregate.
+        let (key, _, salt) = disclosures[1].clone();
+        assert!(!aggregated.verify_disclosed(&[(key.clone(), 43.into(), salt)]));
+        let (_, value, salt) = disclosures[0].clone();
+        assert!(!aggregated.verify_disclosed(&[(key, value, salt)]));
+
+        // Attributes named as block fields are salted too.
+        let mut attributes = IndexMap::new();
+        attributes.insert("u".to_string(), "secret".into());
+        attributes.insert("d".to_string(), "other secret".into());
+        let (reserved, reserved_disclosures) =
+            AggregatedAttributes::new(attributes, &HashFunctionCode::Blake3_256);
+        assert!(reserved.verify_disclosed(&reserved_disclosures));
+        for (key, value, _) in reserved_disclosures {
+            assert!(!reserved.verify_disclosed(&[(key, value, "anything".to_string())]));
+        }
+
+        // Neither does a tampered set of blinded digests.
+        let mut tampered = aggregated;
+        tampered.digests.pop();
+        assert!(!tampered.verify_disclosed(&disclosures[..1]));
+    }
+}
@@ -2,19 +2,30 @@
 //!
 //! See: [`Attestation`]
 
-use said::derivation::HashFunctionCode;
-use said::version::{format::SerializationFormats, SerializationInfo};
-use said::{sad::SAD, SelfAddressingIdentifier};
-use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, collections::BTreeMap, io::Write};
 
-use crate::attributes::InlineAttributes;
-use crate::{Attributes, Authored};
+use chrono::{DateTime, FixedOffset};
+use indexmap::IndexMap;
+use said::derivation::{HashFunction, HashFunctionCode};
+use said::version::{format::SerializationFormats, Encode, SerializationInfo};
+use said::{
+    sad::{DerivationCode, SAD},
+    SelfAddressingIdentifier,
+};
+use serde::{de::IgnoredAny, Deserialize, Serialize};
+use serde_json::value::RawValue;
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SAD)]
-#[version(protocol = "ACDC", major = 1, minor = 0)]
+use crate::attributes::{AttributesBlock, InlineAttributes};
+use crate::edges::{Edge, EdgesBlock};
+use crate::error::Error;
+use crate::rules::{Rules, RulesBlock};
+use crate::salt::{new_salt, salt_len};
+use crate::strict::FirstDuplicateKey;
+use crate::{canonical, stream, Attributes, Authored};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Attestation {
     /// Digest of attestation
-    #[said]
     #[serde(rename = "d")]
     pub digest: Option<SelfAddressingIdentifier>,
 
@@ -23,108 +34,913 @@ pub struct Attestation {
     pub issuer: String,
 
     /// Issuance and/or revocation, transfer, or retraction registry for ACDC
-    /// derived from Issuer Identifier.
-    #[serde(rename = "ri")]
-    pub registry_identifier: String,
+    /// derived from Issuer Identifier. Serialized as empty string when there is
+    /// no registry.
+    #[serde(rename = "ri", with = "registry_identifier", default)]
+    pub registry_identifier: Option<SelfAddressingIdentifier>,
 
     /// Schema SAID.
     #[serde(rename = "s")]
     pub schema: String,
 
-    /// Attributes.
+    /// Attributes. Use [`Attestation::set_attributes`] to change them, as
+    /// mutating the field directly leaves stale digests behind.
     #[serde(rename = "a")]
     pub attrs: Attributes,
+
+    /// Edges to other attestations.
+    #[serde(rename = "e", skip_serializing_if = "Option::is_none", default)]
+    pub edges: Option<EdgesBlock>,
+
+    /// Rules (delegation/consent/license/data agreement) under which data are
+    /// shared.
+    #[serde(rename = "r", skip_serializing_if = "Option::is_none", default)]
+    pub rules: Option<Rules>,
     // /// Provenance chain.
     // #[serde(rename = "p")]
     // pub prov_chain: Vec<String>,
+}
 
-    // /// Rules rules/delegation/consent/license/data agreement under which data are shared.
-    // #[serde(rename = "r")]
-    // pub rules: Vec<serde_json::Value>,
+/// (De)serialization of optional registry identifier, where no registry is
+/// represented by empty string.
+pub(crate) mod registry_identifier {
+    use said::SelfAddressingIdentifier;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        ri: &Option<SelfAddressingIdentifier>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match ri {
+            Some(ri) => serializer.serialize_str(&ri.to_string()),
+            None => serializer.serialize_str(""),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<SelfAddressingIdentifier>, D::Error> {
+        match String::deserialize(deserializer)?.as_str() {
+            "" => Ok(None),
+            ri => ri.parse().map(Some).map_err(de::Error::custom),
+        }
+    }
+}
+
+/// Borrowed serialization of [`Attestation`] with its version string.
+#[derive(Serialize)]
+struct AttestationDerivation<'a> {
+    #[serde(rename = "v")]
+    version: SerializationInfo,
+    #[serde(rename = "d")]
+    digest: String,
+    #[serde(rename = "i")]
+    issuer: &'a str,
+    #[serde(rename = "ri", with = "registry_identifier")]
+    registry_identifier: &'a Option<SelfAddressingIdentifier>,
+    #[serde(rename = "s")]
+    schema: &'a str,
+    #[serde(rename = "a")]
+    attrs: &'a Attributes,
+    #[serde(rename = "e", skip_serializing_if = "Option::is_none")]
+    edges: &'a Option<EdgesBlock>,
+    #[serde(rename = "r", skip_serializing_if = "Option::is_none")]
+    rules: &'a Option<Rules>,
 }
 
 impl Attestation {
     pub fn new_public_targeted(
         issuer: &str,
         target_id: &str,
-        registry_identifier: String,
+        registry_identifier: Option<SelfAddressingIdentifier>,
         schema: String,
         attr: InlineAttributes,
+        format: &SerializationFormats,
     ) -> Self {
         let mut acdc = Self {
             digest: None,
             registry_identifier,
             issuer: issuer.to_string(),
             schema,
-            attrs: attr.to_targeted_public_block(target_id.to_string()),
+            attrs: attr.to_targeted_public_block(target_id.to_string(), format),
+            edges: None,
+            rules: None,
             // prov_chain: Vec::new(),
             // rules: Vec::new(),
         };
         // Compute digest and replace `d` field with SAID.
-        acdc.compute_digest(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON);
+        acdc.compute_digest(&HashFunctionCode::Blake3_256, format);
         acdc
     }
 
     pub fn new_public_untargeted(
         issuer: &str,
-        registry_identifier: String,
+        registry_identifier: Option<SelfAddressingIdentifier>,
         schema: String,
         attr: InlineAttributes,
+        format: &SerializationFormats,
     ) -> Self {
         let mut acdc = Self {
             digest: None,
             registry_identifier,
             issuer: issuer.to_string(),
             schema,
-            attrs: attr.to_untargeted_public_block(),
+            attrs: attr.to_untargeted_public_block(format),
+            edges: None,
+            rules: None,
             // prov_chain: Vec::new(),
             // rules: Vec::new(),
         };
         // Compute digest and replace `d` field with SAID.
-        acdc.compute_digest(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON);
+        acdc.compute_digest(&HashFunctionCode::Blake3_256, format);
         acdc
     }
 
     pub fn new_private_targeted(
         issuer: &str,
         target_id: &str,
-        registry_identifier: String,
+        registry_identifier: Option<SelfAddressingIdentifier>,
         schema: String,
         attr: InlineAttributes,
+        format: &SerializationFormats,
     ) -> Self {
         let mut acdc = Self {
             digest: None,
             registry_identifier,
             issuer: issuer.to_string(),
             schema,
-            attrs: attr.to_targeted_private_block(target_id.to_string()),
+            attrs: attr.to_targeted_private_block(target_id.to_string(), format),
+            edges: None,
+            rules: None,
             // prov_chain: Vec::new(),
             // rules: Vec::new(),
         };
         // Compute digest and replace `d` field with SAID.
-        acdc.compute_digest(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON);
+        acdc.compute_digest(&HashFunctionCode::Blake3_256, format);
         acdc
     }
 
     pub fn new_private_untargeted(
         issuer: &str,
-        registry_identifier: String,
+        registry_identifier: Option<SelfAddressingIdentifier>,
         schema: String,
         attr: InlineAttributes,
+        format: &SerializationFormats,
     ) -> Self {
         let mut acdc = Self {
             digest: None,
             registry_identifier,
             issuer: issuer.to_string(),
             schema,
-            attrs: attr.to_untargeted_private_block(),
+            attrs: attr.to_untargeted_private_block(format),
+            edges: None,
+            rules: None,
             // prov_chain: Vec::new(),
             // rules: Vec::new(),
         };
         // Compute digest and replace `d` field with SAID.
-        acdc.compute_digest(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON);
+        acdc.compute_digest(&HashFunctionCode::Blake3_256, format);
+        acdc
+    }
+
+    /// Attestation without attributes, i.e. untargeted public attributes
+    /// block with empty data. Its SAID depends only on the remaining fields.
+    pub fn new_empty(
+        issuer: &str,
+        registry_identifier: Option<SelfAddressingIdentifier>,
+        schema: String,
+        format: &SerializationFormats,
+    ) -> Self {
+        Self::new_public_untargeted(
+            issuer,
+            registry_identifier,
+            schema,
+            InlineAttributes::default(),
+            format,
+        )
+    }
+
+    /// Metadata attestation, with attributes compacted to the SAID of their
+    /// block. Lets the holder offer what can be disclosed without revealing
+    /// attribute values, as in graduated disclosure. Scalar attributes have
+    /// no block to compact and are kept.
+    ///
+    /// Metadata carries only the attributes SAID: it has neither edges nor
+    /// rules, so its SAID doesn't commit to those of the full attestation.
+    pub fn new_metadata(
+        issuer: &str,
+        registry_identifier: Option<SelfAddressingIdentifier>,
+        schema: String,
+        attrs: &Attributes,
+        format: &SerializationFormats,
+    ) -> Self {
+        let attrs_said = match attrs {
+            Attributes::Inline(block) => match &block.said {
+                Some(said) => said.clone(),
+                None => {
+                    let mut block = block.clone();
+                    block.compute_digest(&HashFunctionCode::Blake3_256, format);
+                    block.said.unwrap()
+                }
+            },
+            Attributes::External(said) => said.clone(),
+            Attributes::Scalar(value) => {
+                return Self::new_scalar(issuer, registry_identifier, schema, value.clone(), format)
+            }
+        };
+        Self::new_compact(issuer, registry_identifier, schema, attrs_said, format)
+    }
+
+    /// Most compact attestation, with attributes given only by `attrs_said`
+    /// of the attributes block. Attestation SAID binds to that bare SAID.
+    /// Edges and rules are left out, see [`Attestation::new_metadata`].
+    pub fn new_compact(
+        issuer: &str,
+        registry_identifier: Option<SelfAddressingIdentifier>,
+        schema: String,
+        attrs_said: SelfAddressingIdentifier,
+        format: &SerializationFormats,
+    ) -> Self {
+        let mut acdc = Self {
+            digest: None,
+            registry_identifier,
+            issuer: issuer.to_string(),
+            schema,
+            attrs: Attributes::External(attrs_said),
+            edges: None,
+            rules: None,
+        };
+        // Compute digest and replace `d` field with SAID.
+        acdc.compute_digest(&HashFunctionCode::Blake3_256, format);
         acdc
     }
+
+    /// Attestation with single scalar `value` (e.g. hash string) in place of
+    /// attributes block, for schemas defining `a` that way. String which
+    /// parses as SAID makes external attributes, as it would be read back.
+    pub fn new_scalar(
+        issuer: &str,
+        registry_identifier: Option<SelfAddressingIdentifier>,
+        schema: String,
+        value: serde_json::Value,
+        format: &SerializationFormats,
+    ) -> Self {
+        let mut acdc = Self {
+            digest: None,
+            registry_identifier,
+            issuer: issuer.to_string(),
+            schema,
+            attrs: match value.as_str().map(str::parse) {
+                Some(Ok(said)) => Attributes::External(said),
+                _ => Attributes::Scalar(value),
+            },
+            edges: None,
+            rules: None,
+        };
+        // Compute digest and replace `d` field with SAID.
+        acdc.compute_digest(&HashFunctionCode::Blake3_256, format);
+        acdc
+    }
+
+    /// Replace attributes and recompute both attribute block and attestation
+    /// SAIDs. Target and salt of current inline block are kept; attestation
+    /// with external or scalar attributes gets an untargeted public block.
+    pub fn set_attributes(
+        &mut self,
+        attrs: InlineAttributes,
+        code: &HashFunctionCode,
+        format: &SerializationFormats,
+    ) {
+        let (target, uuid) = match &self.attrs {
+            Attributes::Inline(block) => (block.target.clone(), block.uuid.clone()),
+            Attributes::External(_) | Attributes::Scalar(_) => (None, None),
+        };
+        let mut block = AttributesBlock {
+            said: None,
+            target,
+            uuid,
+            data: attrs,
+        };
+        block.compute_digest(code, format);
+        self.attrs = Attributes::Inline(block);
+        self.compute_digest(code, format);
+    }
+
+    /// Replace rules and recompute attestation SAID. Inline rules block is
+    /// expected to have its SAID computed already.
+    pub fn set_rules(
+        &mut self,
+        rules: Rules,
+        code: &HashFunctionCode,
+        format: &SerializationFormats,
+    ) {
+        self.rules = Some(rules);
+        self.compute_digest(code, format);
+    }
+
+    /// Set registry identifier and recompute attestation SAID.
+    pub fn with_registry(
+        mut self,
+        registry_identifier: SelfAddressingIdentifier,
+        code: &HashFunctionCode,
+        format: &SerializationFormats,
+    ) -> Self {
+        self.registry_identifier = Some(registry_identifier);
+        self.compute_digest(code, format);
+        self
+    }
+
+    /// Copy of private attestation with fresh salt in attributes block and
+    /// recomputed SAIDs, unlinkable to the original. New salt is of the same
+    /// length as the old one, or of [`crate::salt::new_uuid`] length if the
+    /// old one wasn't generated by [`crate::salt`]. Attestations without
+    /// inline salted attributes can't be rotated.
+    pub fn rotate_salt(
+        &self,
+        code: &HashFunctionCode,
+        format: &SerializationFormats,
+    ) -> Result<Attestation, Error> {
+        let mut rotated = self.clone();
+        match &mut rotated.attrs {
+            Attributes::Inline(block) if block.uuid.is_some() => {
+                let len = block.uuid.as_deref().and_then(salt_len).unwrap_or(16);
+                block.uuid = Some(new_salt(len)?);
+                block.compute_digest(code, format);
+            }
+            _ => return Err(Error::NoSalt),
+        }
+        rotated.compute_digest(code, format);
+        Ok(rotated)
+    }
+
+    /// Replace edges and recompute both edges block and attestation SAIDs.
+    /// Edge named `d` is rejected, see [`EdgesBlock::new`].
+    pub fn set_edges(
+        &mut self,
+        edges: IndexMap<String, Edge>,
+        code: &HashFunctionCode,
+        format: &SerializationFormats,
+    ) -> Result<(), Error> {
+        self.edges = Some(EdgesBlock::new(edges, code, format)?);
+        self.compute_digest(code, format);
+        Ok(())
+    }
+
+    /// Check that every inline SAID'd section (`a`, `e`, `r`) has its SAID
+    /// computed, which is easy to miss when constructing attestation by hand.
+    pub fn ensure_sections_computed(&self) -> Result<(), Error> {
+        if let Attributes::Inline(AttributesBlock { said: None, .. }) = &self.attrs {
+            return Err(Error::UncomputedSection("a"));
+        }
+        if let Some(EdgesBlock { said: None, .. }) = &self.edges {
+            return Err(Error::UncomputedSection("e"));
+        }
+        if let Some(Rules::Inline(RulesBlock { said: None, .. })) = &self.rules {
+            return Err(Error::UncomputedSection("r"));
+        }
+        Ok(())
+    }
+
+    /// Check if attestation SAID and SAIDs of inline attributes, edges and
+    /// rules blocks bind to their content.
+    pub fn verify(&self, format: &SerializationFormats) -> bool {
+        let attrs_verified = match &self.attrs {
+            Attributes::Inline(block) => block.verify(format),
+            Attributes::External(_) | Attributes::Scalar(_) => true,
+        };
+        let edges_verified = self.edges.as_ref().is_none_or(|edges| edges.verify(format));
+        let rules_verified = match &self.rules {
+            Some(Rules::Inline(block)) => block.verify(format),
+            Some(Rules::External(_)) | None => true,
+        };
+        let sections_verified = attrs_verified && edges_verified && rules_verified;
+        match &self.digest {
+            Some(digest) => {
+                let code = digest.derivation.clone().into();
+                sections_verified && digest.verify_binding(&self.derivation_data(&code, format))
+            }
+            None => false,
+        }
+    }
+
+    /// SAIDs of attestations referenced by edges, in edge order. Attestation
+    /// referenced under several edge names is listed once. Empty without
+    /// edges section.
+    pub fn links(&self) -> Vec<SelfAddressingIdentifier> {
+        let mut links: Vec<SelfAddressingIdentifier> = Vec::new();
+        for edge in self.edges.iter().flat_map(|block| block.edges.values()) {
+            if !links.contains(&edge.node) {
+                links.push(edge.node.clone());
+            }
+        }
+        links
+    }
+
+    /// SAIDs of SAID'd sections by their field name, so a section can be
+    /// referenced in disclosure negotiation without revealing it. Works for
+    /// both compact and full form.
+    pub fn section_saids(&self) -> BTreeMap<&'static str, SelfAddressingIdentifier> {
+        let mut saids = BTreeMap::new();
+        let attrs_said = match &self.attrs {
+            Attributes::Inline(block) => block.said.clone(),
+            Attributes::External(said) => Some(said.clone()),
+            Attributes::Scalar(_) => None,
+        };
+        if let Some(said) = attrs_said {
+            saids.insert("a", said);
+        }
+        if let Some(said) = self.edges.as_ref().and_then(|edges| edges.said.clone()) {
+            saids.insert("e", said);
+        }
+        if let Some(said) = self.rules.as_ref().and_then(Rules::said) {
+            saids.insert("r", said.clone());
+        }
+        saids
+    }
+
+    /// Issuance datetime, taken from RFC 3339 `dt` field of inline
+    /// attributes.
+    pub fn issuance_datetime(&self) -> Option<DateTime<FixedOffset>> {
+        match &self.attrs {
+            Attributes::Inline(block) => block
+                .get("dt")
+                .and_then(|dt| dt.as_str())
+                .and_then(|dt| DateTime::parse_from_rfc3339(dt).ok()),
+            Attributes::External(_) | Attributes::Scalar(_) => None,
+        }
+    }
+
+    /// Schema SAID parsed from `s` field.
+    pub fn schema_said(&self) -> Result<SelfAddressingIdentifier, Error> {
+        self.schema.parse().map_err(Error::SchemaSaidInvalid)
+    }
+
+    /// Grouping key of attestations by their type, i.e. schema SAID. `None`
+    /// if `s` field isn't a valid SAID.
+    pub fn schema_family(&self) -> Option<SelfAddressingIdentifier> {
+        self.schema_said().ok()
+    }
+
+    /// Check if both attestations have the same, valid schema SAID.
+    pub fn schema_matches(&self, other: &Attestation) -> bool {
+        match (self.schema_family(), other.schema_family()) {
+            (Some(schema), Some(other_schema)) => schema == other_schema,
+            _ => false,
+        }
+    }
+
+    /// References needed to dereference issuer and schema of attestation with
+    /// DID infrastructure. Issuer AID is exposed as `did:keri` DID.
+    pub fn resolution_reference(&self) -> Result<ResolutionReference, Error> {
+        Ok(ResolutionReference {
+            id: self.digest.clone().ok_or(Error::MissingDigest)?,
+            issuer: format!("did:keri:{}", self.issuer),
+            schema: self.schema_said()?,
+        })
+    }
+
+    /// Check that targeting of inline attributes block matches its JSON
+    /// schema, found at `properties.a` of attestation `schema` (the object
+    /// alternative, if it's a `oneOf`). Block listing `i` as required must be
+    /// targeted, and closed block (`additionalProperties: false`) without `i`
+    /// property must be untargeted. External and scalar attributes aren't
+    /// checked.
+    pub fn validate_targeting(&self, schema: &serde_json::Value) -> Result<(), Error> {
+        let block = match &self.attrs {
+            Attributes::Inline(block) => block,
+            Attributes::External(_) | Attributes::Scalar(_) => return Ok(()),
+        };
+        let attrs_schema = &schema["properties"]["a"];
+        let attrs_schema = attrs_schema["oneOf"]
+            .as_array()
+            .and_then(|alternatives| {
+                alternatives
+                    .iter()
+                    .find(|alternative| alternative["type"] == "object")
+            })
+            .unwrap_or(attrs_schema);
+
+        let target_required = attrs_schema["required"]
+            .as_array()
+            .is_some_and(|required| required.iter().any(|field| field == "i"));
+        let target_allowed = attrs_schema["additionalProperties"] != false
+            || attrs_schema["properties"].get("i").is_some();
+        match &block.target {
+            None if target_required => Err(Error::TargetRequired),
+            Some(_) if !target_allowed => Err(Error::TargetNotAllowed),
+            _ => Ok(()),
+        }
+    }
+
+    /// Parse attestation serialized in any supported format, detected from its
+    /// version string.
+    ///
+    /// Version string has to be of `ACDC` protocol, and size it declares must
+    /// be equal to the length of `data`, otherwise message was truncated or
+    /// tampered with.
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        let version = version_info(data)?;
+        if version.protocol_code != PROTOCOL {
+            return Err(Error::WrongProtocol(version.protocol_code));
+        }
+        if version.size != data.len() {
+            return Err(Error::SizeMismatch {
+                declared: version.size,
+                actual: data.len(),
+            });
+        }
+        match version.kind {
+            SerializationFormats::JSON => parse_json(data),
+            SerializationFormats::CBOR => Ok(serde_cbor::from_slice(data)?),
+            SerializationFormats::MGPK => Ok(rmp_serde::from_slice(data)?),
+        }
+    }
+
+    /// Parse JSON attestation, rejecting objects with duplicated keys anywhere
+    /// in the document, including the attributes block, and unknown top-level
+    /// fields.
+    ///
+    /// Plain `serde_json` parsing keeps the last duplicated value and ignores
+    /// unknown fields, so differing wire representations could map to the
+    /// same parsed attestation, with data not covered by its SAID.
+    pub fn parse_strict(s: &str) -> Result<Self, Error> {
+        let FirstDuplicateKey(duplicate) =
+            serde_json::from_str(s).map_err(Error::AttestationJsonInvalid)?;
+        if let Some(key) = duplicate {
+            return Err(Error::DuplicateKey(key));
+        }
+        let fields: IndexMap<String, IgnoredAny> =
+            serde_json::from_str(s).map_err(Error::AttestationJsonInvalid)?;
+        if let Some(key) = fields
+            .into_keys()
+            .find(|key| !FIELDS.contains(&key.as_str()))
+        {
+            return Err(Error::UnknownField(key));
+        }
+        serde_json::from_str(s).map_err(Error::AttestationJsonInvalid)
+    }
+
+    /// Bytes SAID of attestation is computed over: its serialization in
+    /// `format` with `d` field filled with dummy string of `code` length.
+    /// Check SAID binding with
+    /// `digest.verify_binding(&attestation.derivation_data(code, format))`,
+    /// using derivation code of `digest`.
+    pub fn derivation_data(
+        &self,
+        code: &HashFunctionCode,
+        format: &SerializationFormats,
+    ) -> Vec<u8> {
+        AttestationDerivation::new(self, "#".repeat(code.full_size()))
+            .into_bytes(format)
+            .expect("attestation is serializable")
+    }
+
+    /// Versioned serialization of attestation in given format. With `digest`
+    /// unset, `d` field is filled with dummy string of `code` length, which
+    /// are the same bytes as [`Attestation::derivation_data`] returns, which
+    /// [`SAD`] implementation uses. Otherwise these are the bytes to transmit
+    /// or sign, as [`Encode`] implementation returns.
+    pub fn canonical_bytes(
+        &self,
+        code: &HashFunctionCode,
+        format: &SerializationFormats,
+    ) -> Result<Vec<u8>, Error> {
+        AttestationDerivation::with_digest_or_dummy(self, code).into_bytes(format)
+    }
+
+    /// Exact bytes to be signed by external signer (HSM, KMS): the encoded
+    /// attestation, same as produced by [`Encode::encode`]. Attestation
+    /// digest has to be computed first.
+    pub fn signing_payload(
+        &self,
+        code: &HashFunctionCode,
+        format: &SerializationFormats,
+    ) -> Result<Vec<u8>, Error> {
+        if self.digest.is_none() {
+            return Err(Error::MissingDigest);
+        }
+        self.canonical_bytes(code, format)
+    }
+
+    /// Write [`Attestation::canonical_bytes`] directly into `writer`, without
+    /// intermediate buffer.
+    pub fn encode_to<W: Write>(
+        &self,
+        writer: &mut W,
+        code: &HashFunctionCode,
+        format: &SerializationFormats,
+    ) -> Result<(), Error> {
+        AttestationDerivation::with_digest_or_dummy(self, code).write_to(writer, format)
+    }
+
+    /// Compute attestation SAID feeding derivation data to the hasher in
+    /// chunks, so large attribute blocks are never serialized in memory as a
+    /// whole. Resulting SAID is the same as the one set by
+    /// [`SAD::compute_digest`].
+    pub fn compute_digest_streaming(
+        &mut self,
+        code: &HashFunctionCode,
+        format: &SerializationFormats,
+    ) -> Result<(), Error> {
+        let derivation =
+            AttestationDerivation::new(self, "#".repeat(code.full_size())).sized(format)?;
+        self.digest = Some(stream::derive(&derivation, code, format)?);
+        Ok(())
+    }
+
+    /// Derivation data serialized with JCS (RFC 8785). See
+    /// [`Attestation::compute_digest_canonical`].
+    pub fn derivation_data_canonical(&self, code: &HashFunctionCode) -> Result<Vec<u8>, Error> {
+        let derivation = AttestationDerivation::new(self, "#".repeat(code.full_size()));
+        derivation.into_canonical()
+    }
+
+    /// Compute attestation SAID over JCS (RFC 8785) serialization instead of
+    /// plain `serde_json` output, so it matches other implementations for the
+    /// same logical data. Attestations digested this way must be transmitted
+    /// in [`Attestation::encode_canonical`] form.
+    pub fn compute_digest_canonical(&mut self, code: &HashFunctionCode) -> Result<(), Error> {
+        let derivation_data = self.derivation_data_canonical(code)?;
+        self.digest = Some(HashFunction::from(code.clone()).derive(&derivation_data));
+        Ok(())
+    }
+
+    /// Versioned JCS (RFC 8785) serialization of attestation, except that
+    /// version string is moved to the front, so it parses back with
+    /// [`Attestation::parse`]. Member order doesn't change the SAID, which is
+    /// computed over plain JCS.
+    pub fn encode_canonical(&self, code: &HashFunctionCode) -> Result<Vec<u8>, Error> {
+        AttestationDerivation::with_digest_or_dummy(self, code).into_canonical_encoding()
+    }
+}
+
+/// Protocol code of attestation version string.
+pub(crate) const PROTOCOL: &str = "ACDC";
+
+/// Length of version string, e.g. `ACDC10JSON00011c_`.
+pub(crate) const VERSION_STRING_LEN: usize = 17;
+
+/// Top-level fields of serialized attestation, all covered by its SAID.
+const FIELDS: [&str; 8] = ["v", "d", "i", "ri", "s", "a", "e", "r"];
+
+/// Version string is expected within this many leading bytes of serialized
+/// message, right after the map header and `v` key.
+const VERSION_STRING_SEARCH_LEN: usize = 32;
+
+/// Find and parse version string at the beginning of serialized message.
+fn version_info(data: &[u8]) -> Result<SerializationInfo, Error> {
+    let head = &data[..data.len().min(VERSION_STRING_SEARCH_LEN)];
+    head.windows(VERSION_STRING_LEN)
+        .find_map(|window| parse_version_string(std::str::from_utf8(window).ok()?))
+        .ok_or(Error::MissingVersionString)
+}
+
+/// Parse version string of `PPPPvvKKKKssssss_` layout. `said`'s own parser
+/// reads size as `u16`, which can't represent all 6 hex digits.
+fn parse_version_string(s: &str) -> Option<SerializationInfo> {
+    // Fields are sliced by byte index below.
+    if s.len() != VERSION_STRING_LEN || !s.is_ascii() {
+        return None;
+    }
+    let is_hex = |s: &str| s.bytes().all(|b| b.is_ascii_hexdigit());
+    let (protocol, major, minor) = (&s[..4], &s[4..5], &s[5..6]);
+    let (kind, size) = (&s[6..10], &s[10..16]);
+    if !protocol.bytes().all(|b| b.is_ascii_uppercase())
+        || !is_hex(major)
+        || !is_hex(minor)
+        || !is_hex(size)
+        || !s.ends_with('_')
+    {
+        return None;
+    }
+    Some(SerializationInfo::new(
+        protocol.to_string(),
+        u8::from_str_radix(major, 16).ok()?,
+        u8::from_str_radix(minor, 16).ok()?,
+        kind.parse().ok()?,
+        usize::from_str_radix(size, 16).ok()?,
+    ))
+}
+
+/// Deserialize JSON attestation. Only parsing goes through `simd-json` when
+/// the feature is enabled, serialization used for SAIDs stays `serde_json`.
+#[cfg(not(feature = "simd-json"))]
+fn parse_json(data: &[u8]) -> Result<Attestation, Error> {
+    serde_json::from_slice(data).map_err(Error::AttestationJsonInvalid)
+}
+
+#[cfg(feature = "simd-json")]
+fn parse_json(data: &[u8]) -> Result<Attestation, Error> {
+    // `simd-json` parses in place. Errors are rewrapped so both paths report
+    // `Error::AttestationJsonInvalid`.
+    simd_json::serde::from_slice(&mut data.to_vec())
+        .map_err(|e| Error::AttestationJsonInvalid(serde::de::Error::custom(e)))
+}
+
+/// Canonical reference object linking attestation SAID with its issuer and
+/// schema, see [`Attestation::resolution_reference`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolutionReference {
+    /// SAID of attestation.
+    pub id: SelfAddressingIdentifier,
+
+    /// Issuer DID, used to look up its KEL.
+    pub issuer: String,
+
+    /// Schema SAID.
+    pub schema: SelfAddressingIdentifier,
+}
+
+impl<'a> AttestationDerivation<'a> {
+    fn new(acdc: &'a Attestation, digest: String) -> Self {
+        Self {
+            version: SerializationInfo::new_empty(
+                PROTOCOL.to_string(),
+                1,
+                0,
+                SerializationFormats::JSON,
+            ),
+            digest,
+            issuer: &acdc.issuer,
+            registry_identifier: &acdc.registry_identifier,
+            schema: &acdc.schema,
+            attrs: &acdc.attrs,
+            edges: &acdc.edges,
+            rules: &acdc.rules,
+        }
+    }
+
+    /// Derivation with attestation digest, or dummy one if it isn't computed.
+    fn with_digest_or_dummy(acdc: &'a Attestation, code: &HashFunctionCode) -> Self {
+        let digest = match &acdc.digest {
+            Some(digest) => digest.to_string(),
+            None => "#".repeat(code.full_size()),
+        };
+        Self::new(acdc, digest)
+    }
+
+    /// Set version string kind to `format` and size to serialized length.
+    fn sized(mut self, format: &SerializationFormats) -> Result<Self, Error> {
+        self.version.kind = *format;
+        // Size field has fixed width, so it doesn't change the length.
+        self.version.size = stream::serialized_len(&self, format)?;
+        Ok(self)
+    }
+
+    /// Serialization in `format`, with version string carrying its size.
+    fn into_bytes(self, format: &SerializationFormats) -> Result<Vec<u8>, Error> {
+        let mut out = Vec::new();
+        self.write_to(&mut out, format)?;
+        Ok(out)
+    }
+
+    /// Write serialization in `format`, with version string carrying its
+    /// size, into `writer`.
+    fn write_to<W: Write>(
+        self,
+        writer: &mut W,
+        format: &SerializationFormats,
+    ) -> Result<(), Error> {
+        stream::serialize_into(writer, &self.sized(format)?, format)
+    }
+
+    /// JCS serialization with version string carrying its size.
+    fn into_canonical(mut self) -> Result<Vec<u8>, Error> {
+        self.version.size = canonical::to_jcs_vec(&self)?.len();
+        canonical::to_jcs_vec(&self)
+    }
+
+    /// [`AttestationDerivation::into_canonical`] with version string moved to
+    /// the front, where [`Attestation::parse`] looks for it. Size is the same.
+    fn into_canonical_encoding(mut self) -> Result<Vec<u8>, Error> {
+        self.version.size = canonical::to_jcs_vec(&self)?.len();
+        canonical::to_jcs_vec_with_first(&self, "v")
+    }
+}
+
+/// Borrowed view of serialized [`Attestation`], deserialized without
+/// allocating its text fields. Attributes are kept as raw JSON until
+/// converted with [`AttestationRef::to_owned`]. Fields containing JSON escape
+/// sequences can't be borrowed and fail to deserialize.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct AttestationRef<'a> {
+    #[serde(rename = "d")]
+    pub digest: Option<&'a str>,
+
+    #[serde(rename = "i")]
+    pub issuer: &'a str,
+
+    #[serde(rename = "ri", default)]
+    pub registry_identifier: &'a str,
+
+    #[serde(rename = "s")]
+    pub schema: &'a str,
+
+    #[serde(rename = "a", borrow)]
+    pub attrs: &'a RawValue,
+
+    #[serde(rename = "e", borrow, default)]
+    pub edges: Option<&'a RawValue>,
+
+    #[serde(rename = "r", borrow, default)]
+    pub rules: Option<&'a RawValue>,
+}
+
+impl<'a> AttestationRef<'a> {
+    /// Convert to owned [`Attestation`], parsing SAIDs and attributes.
+    pub fn to_owned(&self) -> Result<Attestation, Error> {
+        let digest = self
+            .digest
+            .map(|digest| digest.parse().map_err(Error::SaidInvalid))
+            .transpose()?;
+        let registry_identifier = match self.registry_identifier {
+            "" => None,
+            ri => Some(ri.parse().map_err(Error::SaidInvalid)?),
+        };
+        Ok(Attestation {
+            digest,
+            issuer: self.issuer.to_string(),
+            registry_identifier,
+            schema: self.schema.to_string(),
+            attrs: serde_json::from_str(self.attrs.get()).map_err(Error::AttestationJsonInvalid)?,
+            edges: self
+                .edges
+                .map(|edges| serde_json::from_str(edges.get()))
+                .transpose()
+                .map_err(Error::AttestationJsonInvalid)?,
+            rules: self
+                .rules
+                .map(|rules| serde_json::from_str(rules.get()))
+                .transpose()
+                .map_err(Error::AttestationJsonInvalid)?,
+        })
+    }
+}
+
+/// Empty attestation to build up field by field: no issuer, schema or
+/// registry and empty public untargeted attributes, with computed SAIDs.
+impl Default for Attestation {
+    fn default() -> Self {
+        Self::new_public_untargeted(
+            "",
+            None,
+            String::new(),
+            InlineAttributes::default(),
+            &SerializationFormats::JSON,
+        )
+    }
+}
+
+// `SAD` and `Encode` are implemented by hand on top of `derivation_data` and
+// `canonical_bytes`, which serialize the same borrowed view of attestation,
+// so encoded and SAID'd bytes come from the same serialization.
+impl SAD for Attestation {
+    fn compute_digest(&mut self, code: &HashFunctionCode, format: &SerializationFormats) {
+        let derivation_data = self.derivation_data(code, format);
+        self.digest = Some(HashFunction::from(code.clone()).derive(&derivation_data));
+    }
+
+    fn derivation_data(&self, code: &HashFunctionCode, format: &SerializationFormats) -> Vec<u8> {
+        Attestation::derivation_data(self, code, format)
+    }
+}
+
+/// `said`'s error can't hold a source, so the crate error is kept only as its
+/// message. Use [`Attestation::canonical_bytes`] to get it with the source.
+impl Encode for Attestation {
+    fn encode(
+        &self,
+        code: &HashFunctionCode,
+        format: &SerializationFormats,
+    ) -> Result<Vec<u8>, said::version::error::Error> {
+        self.canonical_bytes(code, format)
+            .map_err(|e| said::version::error::Error::SerializationError(e.to_string()))
+    }
+}
+
+impl Ord for Attestation {
+    /// Orders by issuance datetime (see [`Attestation::issuance_datetime`]),
+    /// then by SAID. Attestations without issuance datetime sort after those
+    /// with one. As SAID binds attestation content, attestations ordered as
+    /// equal are equal, unless their SAIDs are missing or don't verify.
+    fn cmp(&self, other: &Self) -> Ordering {
+        let by_datetime = match (self.issuance_datetime(), other.issuance_datetime()) {
+            (Some(dt), Some(other_dt)) => dt.cmp(&other_dt),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        };
+        by_datetime.then_with(|| {
+            let said = self.digest.as_ref().map(|d| d.to_string());
+            said.cmp(&other.digest.as_ref().map(|d| d.to_string()))
+        })
+    }
+}
+
+impl PartialOrd for Attestation {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
 impl Authored for Attestation {
@@ -137,11 +953,11 @@ impl Authored for Attestation {
 mod tests {
     use said::{
         derivation::{HashFunction, HashFunctionCode},
-        sad::SerializationFormats,
+        sad::{SerializationFormats, SAD},
         version::Encode,
     };
 
-    use crate::{attributes::InlineAttributes, error::Error, Attestation};
+    use crate::{attributes::InlineAttributes, error::Error, Attestation, Attributes};
 
     #[test]
     pub fn test_attributes_order() -> Result<(), Error> {
@@ -152,11 +968,12 @@ mod tests {
 
         let attestation = Attestation::new_public_untargeted(
             "issuer",
-            "".to_string(),
+            None,
             HashFunction::from(HashFunctionCode::Blake3_256)
                 .derive(&[0; 30])
                 .to_string(),
             data,
+            &SerializationFormats::JSON,
         );
         let encoded = attestation
             .encode(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON)
@@ -173,4 +990,134 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    pub fn test_streaming_digest() -> Result<(), Error> {
+        let mut data = InlineAttributes::default();
+        data.insert("name".to_string(), "Hella".into());
+        data.insert("payload".to_string(), "A".repeat(1 << 16).into());
+
+        let mut attestation = Attestation::new_private_targeted(
+            "issuer",
+            "target",
+            None,
+            HashFunction::from(HashFunctionCode::Blake3_256)
+                .derive(&[0; 30])
+                .to_string(),
+            data,
+            &SerializationFormats::JSON,
+        );
+
+        for code in [HashFunctionCode::Blake3_256, HashFunctionCode::SHA3_512] {
+            for format in [
+                SerializationFormats::JSON,
+                SerializationFormats::CBOR,
+                SerializationFormats::MGPK,
+            ] {
+                if let Attributes::Inline(block) = &mut attestation.attrs {
+                    block.compute_digest(&code, &format);
+                    let expected = block.said.take();
+                    block.compute_digest_streaming(&code, &format)?;
+                    assert_eq!(block.said, expected);
+                }
+
+                attestation.compute_digest(&code, &format);
+                let expected = attestation.digest.take();
+                attestation.compute_digest_streaming(&code, &format)?;
+                assert_eq!(attestation.digest, expected);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_canonical_digest() -> Result<(), Error> {
+        let mut data = InlineAttributes::default();
+        data.insert("score".to_string(), serde_json::json!(1e21));
+        data.insert("name".to_string(), "Hella".into());
+
+        let mut attestation = Attestation::new_public_untargeted(
+            "issuer",
+            None,
+            HashFunction::from(HashFunctionCode::Blake3_256)
+                .derive(&[0; 30])
+                .to_string(),
+            data,
+            &SerializationFormats::JSON,
+        );
+        if let Attributes::Inline(block) = &mut attestation.attrs {
+            block.compute_digest_canonical(&HashFunctionCode::Blake3_256)?;
+        }
+        attestation.compute_digest_canonical(&HashFunctionCode::Blake3_256)?;
+
+        let encoded = attestation.encode_canonical(&HashFunctionCode::Blake3_256)?;
+        let encoded = String::from_utf8(encoded).unwrap();
+        assert!(encoded.starts_with(&format!(
+            r#"{{"v":"ACDC10JSON{:06x}_","a":{{"a":{{"name":"Hella","score":1e+21}},"d":""#,
+            encoded.len()
+        )));
+
+        // Digest binds to the received canonical form.
+        let parsed = Attestation::parse(encoded.as_bytes())?;
+        assert_eq!(parsed, attestation);
+        assert_eq!(parsed.digest, attestation.digest);
+        let digest = parsed.digest.clone().unwrap();
+        assert!(digest
+            .verify_binding(&parsed.derivation_data_canonical(&HashFunctionCode::Blake3_256)?));
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_canonical_vector() -> Result<(), Error> {
+        // Input from RFC 8785, Section 3.2.3, as attribute values.
+        let data: InlineAttributes = serde_json::from_str(
+            r#"{
+                "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+                "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+                "literals": [null, true, false],
+                "\u20ac": "Euro Sign",
+                "\r": "Carriage Return",
+                "\ud83d\ude00": "Emoji",
+                "\ufb33": "Hebrew Letter Dalet With Dagesh"
+            }"#,
+        )
+        .unwrap();
+        let code = HashFunctionCode::Blake3_256;
+        let mut attestation = Attestation::new_public_untargeted(
+            "issuer",
+            None,
+            HashFunction::from(code.clone())
+                .derive(&[0; 30])
+                .to_string(),
+            data,
+            &SerializationFormats::JSON,
+        );
+        let block_said = match &mut attestation.attrs {
+            Attributes::Inline(block) => {
+                block.compute_digest_canonical(&code)?;
+                block.said.clone().unwrap()
+            }
+            _ => unreachable!(),
+        };
+
+        let derivation_data =
+            String::from_utf8(attestation.derivation_data_canonical(&code)?).unwrap();
+        let expected = format!(
+            "{{\"a\":{{\"a\":{{\"\\r\":\"Carriage Return\",\"literals\":[null,true,false],\
+             \"numbers\":[333333333.3333333,1e+30,4.5,0.002,1e-27],\
+             \"string\":\"\u{20ac}$\\u000f\\nA'B\\\"\\\\\\\\\\\"/\",\
+             \"\u{20ac}\":\"Euro Sign\",\"\u{1f600}\":\"Emoji\",\
+             \"\u{fb33}\":\"Hebrew Letter Dalet With Dagesh\"}},\"d\":\"{}\"}},\
+             \"d\":\"{}\",\"i\":\"issuer\",\"ri\":\"\",\"s\":\"{}\",\"v\":\"ACDC10JSON{:06x}_\"}}",
+            block_said,
+            "#".repeat(44),
+            attestation.schema,
+            derivation_data.len()
+        );
+        assert_eq!(derivation_data, expected);
+
+        Ok(())
+    }
 }
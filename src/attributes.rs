@@ -2,16 +2,17 @@ use std::str::FromStr;
 
 use indexmap::IndexMap;
 use said::{
-    derivation::HashFunctionCode, sad::SAD, version::format::SerializationFormats,
+    derivation::{HashFunction, HashFunctionCode},
+    sad::{DerivationCode, SAD},
+    version::format::SerializationFormats,
     SelfAddressingIdentifier,
 };
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize};
 
-use crate::{error::Error, salt::new_uuid};
+use crate::{binding::verify_said, canonical, error::Error, salt::new_uuid, stream, Attestation};
 
-#[derive(Serialize, SAD, Default, Debug, Clone, PartialEq, Deserialize)]
+#[derive(Serialize, Default, Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct AttributesBlock {
-    #[said]
     #[serde(rename = "d")]
     pub said: Option<SelfAddressingIdentifier>,
     #[serde(rename = "i", skip_serializing_if = "Option::is_none")]
@@ -22,81 +23,398 @@ pub struct AttributesBlock {
     pub data: InlineAttributes,
 }
 
+/// Borrowed counterpart of `SAD` derivation data of [`AttributesBlock`].
+#[derive(Serialize)]
+struct AttributesBlockDerivation<'a> {
+    #[serde(rename = "d")]
+    said: String,
+    #[serde(rename = "i", skip_serializing_if = "Option::is_none")]
+    target: Option<&'a String>,
+    #[serde(rename = "u", skip_serializing_if = "Option::is_none")]
+    uuid: Option<&'a String>,
+    #[serde(rename = "a")]
+    data: &'a InlineAttributes,
+}
+
 impl AttributesBlock {
     pub fn attributes(&self) -> IndexMap<String, serde_json::Value> {
         self.data.0.clone()
     }
+
+    /// Iterate over attributes in insertion order, without cloning.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &serde_json::Value)> {
+        self.data.0.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    pub fn get(&self, key: &str) -> Option<&serde_json::Value> {
+        self.data.0.get(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.0.is_empty()
+    }
+
+    /// Block for safe logging, with SAID and target kept and every attribute
+    /// value (and the salt) replaced by `*` characters of the same length.
+    /// Keys and nesting of objects and arrays stay intact.
+    pub fn redacted(&self) -> serde_json::Value {
+        let mut block = serde_json::Map::new();
+        if let Some(said) = &self.said {
+            block.insert("d".to_string(), said.to_string().into());
+        }
+        if let Some(target) = &self.target {
+            block.insert("i".to_string(), target.clone().into());
+        }
+        if let Some(uuid) = &self.uuid {
+            block.insert("u".to_string(), redact(&uuid.clone().into()));
+        }
+        let data = self
+            .iter()
+            .map(|(k, v)| (k.to_string(), redact(v)))
+            .collect();
+        block.insert("a".to_string(), serde_json::Value::Object(data));
+        serde_json::Value::Object(block)
+    }
+
+    /// Reference to this block, for compact attestations. Block SAID must be
+    /// computed first.
+    pub fn externalize(&self) -> Result<Attributes, Error> {
+        self.said
+            .clone()
+            .map(Attributes::External)
+            .ok_or(Error::MissingDigest)
+    }
+
+    /// Block received with its SAID already computed, e.g. by other
+    /// implementation. SAID is checked against block content and kept as is,
+    /// instead of being recomputed.
+    pub fn from_verified(value: serde_json::Value) -> Result<Self, Error> {
+        let block: Self = serde_json::from_value(value).map_err(Error::AttributesJsonInvalid)?;
+        if block.said.is_none() {
+            return Err(Error::MissingDigest);
+        }
+        if !block.verify(&SerializationFormats::JSON) {
+            return Err(Error::SaidMismatch);
+        }
+        Ok(block)
+    }
+
+    /// Attestation embedded as value of `key`, if it parses as one and its
+    /// SAIDs verify independently of the enclosing block.
+    pub fn embedded_acdc(&self, key: &str) -> Option<Attestation> {
+        let acdc: Attestation = serde_json::from_value(self.get(key)?.clone()).ok()?;
+        acdc.verify(&SerializationFormats::JSON).then_some(acdc)
+    }
+
+    /// Check if block SAID binds to its target, salt and attributes.
+    pub fn verify(&self, format: &SerializationFormats) -> bool {
+        verify_said(&self.said, self, format)
+    }
+
+    /// Compute block SAID feeding derivation data to the hasher in chunks.
+    /// Resulting SAID is the same as the one set by [`SAD::compute_digest`].
+    pub fn compute_digest_streaming(
+        &mut self,
+        code: &HashFunctionCode,
+        format: &SerializationFormats,
+    ) -> Result<(), Error> {
+        self.said = Some(stream::derive(&self.derivation(code), code, format)?);
+        Ok(())
+    }
+
+    /// Derivation data serialized with JCS (RFC 8785). See
+    /// [`AttributesBlock::compute_digest_canonical`].
+    pub fn derivation_data_canonical(&self, code: &HashFunctionCode) -> Result<Vec<u8>, Error> {
+        canonical::to_jcs_vec(&self.derivation(code))
+    }
+
+    /// Compute block SAID over JCS (RFC 8785) serialization instead of plain
+    /// `serde_json` output.
+    pub fn compute_digest_canonical(&mut self, code: &HashFunctionCode) -> Result<(), Error> {
+        let derivation_data = self.derivation_data_canonical(code)?;
+        self.said = Some(HashFunction::from(code.clone()).derive(&derivation_data));
+        Ok(())
+    }
+
+    fn derivation(&self, code: &HashFunctionCode) -> AttributesBlockDerivation<'_> {
+        AttributesBlockDerivation {
+            said: "#".repeat(code.full_size()),
+            target: self.target.as_ref(),
+            uuid: self.uuid.as_ref(),
+            data: &self.data,
+        }
+    }
 }
 
-#[derive(Serialize, Default, Debug, Clone, PartialEq, Deserialize)]
+// Implemented by hand, as derived derivation data serializes the block as
+// positional array in MessagePack, while it's transmitted as named map.
+impl SAD for AttributesBlock {
+    fn compute_digest(&mut self, code: &HashFunctionCode, format: &SerializationFormats) {
+        let derivation_data = self.derivation_data(code, format);
+        self.said = Some(HashFunction::from(code.clone()).derive(&derivation_data));
+    }
+
+    fn derivation_data(&self, code: &HashFunctionCode, format: &SerializationFormats) -> Vec<u8> {
+        let mut data = Vec::new();
+        stream::serialize_into(&mut data, &self.derivation(code), format)
+            .expect("attributes block is serializable");
+        data
+    }
+}
+
+/// Parse `text` as value of JSON schema type `t`.
+fn coerce(text: &str, t: &str) -> Option<serde_json::Value> {
+    let parsed = || serde_json::from_str::<serde_json::Value>(text.trim()).ok();
+    match t {
+        "integer" => parsed().filter(|v| v.is_i64() || v.is_u64()),
+        "number" => parsed().filter(serde_json::Value::is_number),
+        "boolean" => parsed().filter(serde_json::Value::is_boolean),
+        "null" => parsed().filter(serde_json::Value::is_null),
+        _ => None,
+    }
+}
+
+/// Replace scalars with `*` of their length, keeping structure.
+fn redact(value: &serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+    match value {
+        Value::Object(map) => {
+            Value::Object(map.iter().map(|(k, v)| (k.clone(), redact(v))).collect())
+        }
+        Value::Array(items) => Value::Array(items.iter().map(redact).collect()),
+        Value::String(s) => "*".repeat(s.chars().count()).into(),
+        scalar => "*".repeat(scalar.to_string().len()).into(),
+    }
+}
+
+#[derive(Serialize, Default, Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct InlineAttributes(IndexMap<String, serde_json::Value>);
 impl InlineAttributes {
-    pub fn to_untargeted_public_block(self) -> Attributes {
+    pub fn to_untargeted_public_block(self, format: &SerializationFormats) -> Attributes {
         let mut attr = AttributesBlock {
             said: None,
             uuid: None,
             target: None,
             data: self,
         };
-        attr.compute_digest(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON);
+        attr.compute_digest(&HashFunctionCode::Blake3_256, format);
         Attributes::Inline(attr)
     }
-    pub fn to_targeted_public_block(self, target: String) -> Attributes {
+    pub fn to_targeted_public_block(
+        self,
+        target: String,
+        format: &SerializationFormats,
+    ) -> Attributes {
         let mut attr = AttributesBlock {
             said: None,
             uuid: None,
             target: Some(target),
             data: self,
         };
-        attr.compute_digest(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON);
+        attr.compute_digest(&HashFunctionCode::Blake3_256, format);
         Attributes::Inline(attr)
     }
 
-    pub fn to_untargeted_private_block(self) -> Attributes {
+    pub fn to_untargeted_private_block(self, format: &SerializationFormats) -> Attributes {
+        self.to_untargeted_private_block_with_salt(new_uuid(), format)
+    }
+
+    /// Private block with caller provided salt, e.g. from [`crate::salt::new_salt`].
+    pub fn to_untargeted_private_block_with_salt(
+        self,
+        salt: String,
+        format: &SerializationFormats,
+    ) -> Attributes {
         let mut attr = AttributesBlock {
             said: None,
-            uuid: Some(new_uuid()),
+            uuid: Some(salt),
             target: None,
             data: self,
         };
-        attr.compute_digest(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON);
+        attr.compute_digest(&HashFunctionCode::Blake3_256, format);
         Attributes::Inline(attr)
     }
-    pub fn to_targeted_private_block(self, target: String) -> Attributes {
+    pub fn to_targeted_private_block(
+        self,
+        target: String,
+        format: &SerializationFormats,
+    ) -> Attributes {
+        self.to_targeted_private_block_with_salt(target, new_uuid(), format)
+    }
+
+    /// Private block with caller provided salt, e.g. from [`crate::salt::new_salt`].
+    pub fn to_targeted_private_block_with_salt(
+        self,
+        target: String,
+        salt: String,
+        format: &SerializationFormats,
+    ) -> Attributes {
         let mut attr = AttributesBlock {
             said: None,
-            uuid: Some(new_uuid()),
+            uuid: Some(salt),
             target: Some(target),
             data: self,
         };
-        attr.compute_digest(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON);
+        attr.compute_digest(&HashFunctionCode::Blake3_256, format);
         Attributes::Inline(attr)
     }
 }
 
+/// Nested attributes section with its own SAID, so it can be disclosed
+/// independently of the enclosing block. It may contain further nested blocks,
+/// which need to be built first, as their SAIDs fold into this one.
+#[derive(Serialize, SAD, Default, Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SaidBlock {
+    #[said]
+    #[serde(rename = "d")]
+    pub said: Option<SelfAddressingIdentifier>,
+    #[serde(flatten)]
+    pub data: InlineAttributes,
+}
+
+impl SaidBlock {
+    /// Block of `data` with computed SAID. Attributes are flattened next to
+    /// SAID, so `d` attribute is rejected.
+    pub fn new(
+        data: InlineAttributes,
+        code: &HashFunctionCode,
+        format: &SerializationFormats,
+    ) -> Result<Self, Error> {
+        if data.0.contains_key("d") {
+            return Err(Error::ReservedKey("d".to_string()));
+        }
+        let mut block = Self { said: None, data };
+        block.compute_digest(code, format);
+        Ok(block)
+    }
+
+    /// Check if block SAID binds to nested attributes.
+    pub fn verify(&self, format: &SerializationFormats) -> bool {
+        verify_said(&self.said, self, format)
+    }
+}
+
 /// Attestation attributes.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+///
+/// Deserialized by the shape of the value: any object has to be a valid
+/// attributes block, string which parses as SAID is external attributes, and
+/// anything else is scalar.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 #[serde(untagged)]
 pub enum Attributes {
     /// Inlined attributes as a JSON object.
     Inline(AttributesBlock),
     /// External attributes identified by their [`SelfAddressingIdentifier`].
     External(SelfAddressingIdentifier),
+    /// Single non-object value in place of attributes block, without SAID of
+    /// its own. Bound only by attestation SAID. Not meant for strings which
+    /// parse as SAID: these serialize the same as `External` and are read
+    /// back as such, see [`crate::Attestation::new_scalar`].
+    Scalar(serde_json::Value),
+}
+
+impl<'de> Deserialize<'de> for Attributes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match serde_json::Value::deserialize(deserializer)? {
+            value @ serde_json::Value::Object(_) => serde_json::from_value(value)
+                .map(Attributes::Inline)
+                .map_err(de::Error::custom),
+            serde_json::Value::String(s) => Ok(match s.parse() {
+                Ok(said) => Attributes::External(said),
+                Err(_) => Attributes::Scalar(s.into()),
+            }),
+            value => Ok(Attributes::Scalar(value)),
+        }
+    }
 }
 
 impl InlineAttributes {
     pub fn insert(&mut self, key: String, value: serde_json::Value) {
         self.0.insert(key, value);
     }
+
+    /// Insert nested block under `key`.
+    pub fn insert_said_block(&mut self, key: String, block: SaidBlock) -> Result<(), Error> {
+        let value =
+            serde_json::to_value(block).map_err(|e| Error::SerializationError(Box::new(e)))?;
+        self.0.insert(key, value);
+        Ok(())
+    }
+
+    /// Convert string values to JSON types declared for them in `properties`
+    /// of attributes `schema`, e.g. `"30"` to `30` for `integer` field, so
+    /// stringly-typed input doesn't change SAID. Fields not described by the
+    /// schema, or allowed to be strings, are left untouched.
+    pub fn coerce_to_schema(&mut self, schema: &serde_json::Value) -> Result<(), Error> {
+        let properties = match schema["properties"].as_object() {
+            Some(properties) => properties,
+            None => return Ok(()),
+        };
+        for (key, value) in self.0.iter_mut() {
+            let (Some(text), Some(property)) = (value.as_str(), properties.get(key)) else {
+                continue;
+            };
+            let types: Vec<&str> = match &property["type"] {
+                serde_json::Value::String(t) => vec![t.as_str()],
+                serde_json::Value::Array(types) => {
+                    types.iter().filter_map(serde_json::Value::as_str).collect()
+                }
+                _ => continue,
+            };
+            if types.contains(&"string") {
+                continue;
+            }
+            *value = types.iter().find_map(|t| coerce(text, t)).ok_or_else(|| {
+                Error::CoercionFailed {
+                    key: key.clone(),
+                    expected: types.join(", "),
+                }
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Nested block stored under `key`, if the value has a `d` field.
+    pub fn said_block(&self, key: &str) -> Option<SaidBlock> {
+        let value = self.0.get(key)?;
+        value.get("d")?;
+        serde_json::from_value(value.clone()).ok()
+    }
+
+    /// Merge `other` attributes into `self`.
+    ///
+    /// New keys are appended in `other` order. On key collision the value from
+    /// `other` wins, but the key keeps its original position. SAID of any block
+    /// built from these attributes needs to be recomputed afterwards.
+    pub fn merge(&mut self, other: InlineAttributes) {
+        self.extend(other.0);
+    }
+}
+
+impl Extend<(String, serde_json::Value)> for InlineAttributes {
+    /// Insert attributes with the same semantics as [`InlineAttributes::merge`].
+    fn extend<T: IntoIterator<Item = (String, serde_json::Value)>>(&mut self, iter: T) {
+        self.0.extend(iter);
+    }
 }
 
 impl FromStr for InlineAttributes {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let attributes: IndexMap<String, serde_json::Value> =
-            serde_json::from_str(s).map_err(|_e| Error::ParseError)?;
-        Ok(Self(attributes))
+        match serde_json::from_str::<IndexMap<String, serde_json::Value>>(s) {
+            Ok(attributes) => Ok(Self(attributes)),
+            // Distinguish well formed JSON which isn't an object from malformed
+            // input.
+            Err(e) => match serde_json::from_str::<serde_json::Value>(s) {
+                Ok(value) if !value.is_object() => Err(Error::AttributesNotObject),
+                _ => Err(Error::AttributesJsonInvalid(e)),
+            },
+        }
     }
 }
 
@@ -104,4 +422,13 @@ impl Attributes {
     pub fn new_inline(attributes: AttributesBlock) -> Self {
         Attributes::Inline(attributes)
     }
+
+    /// Attribute data of inline block, without its SAID, target and salt
+    /// envelope. Use it to hand claims over without leaking the salt.
+    pub fn data(&self) -> Option<&InlineAttributes> {
+        match self {
+            Attributes::Inline(block) => Some(&block.data),
+            Attributes::External(_) | Attributes::Scalar(_) => None,
+        }
+    }
 }
@@ -0,0 +1,22 @@
+//! SAID binding check shared by SAID'd blocks.
+
+use said::{
+    sad::{SerializationFormats, SAD},
+    SelfAddressingIdentifier,
+};
+
+/// Check if `said` of `block` binds to its derivation data in `format`, using
+/// derivation code of `said`. Block without SAID doesn't verify.
+pub(crate) fn verify_said<T: SAD>(
+    said: &Option<SelfAddressingIdentifier>,
+    block: &T,
+    format: &SerializationFormats,
+) -> bool {
+    match said {
+        Some(said) => {
+            let code = said.derivation.clone().into();
+            said.verify_binding(&block.derivation_data(&code, format))
+        }
+        None => false,
+    }
+}
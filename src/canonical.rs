@@ -0,0 +1,194 @@
+//! JSON Canonicalization Scheme (JCS, RFC 8785).
+//!
+//! Opt-in serialization for SAID derivation, which produces the same bytes as
+//! other JCS implementations regardless of how the data were serialized
+//! before. See: [`to_jcs_vec`]
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::Error;
+
+/// Serialize `value` to JCS canonical JSON.
+pub fn to_jcs_vec<T: Serialize>(value: &T) -> Result<Vec<u8>, Error> {
+    let value = serde_json::to_value(value).map_err(|e| Error::SerializationError(Box::new(e)))?;
+    let mut out = String::new();
+    write_value(&mut out, &value)?;
+    Ok(out.into_bytes())
+}
+
+/// Serialize `value` to JCS canonical JSON, except that `first` member of
+/// top-level object is kept at its beginning, e.g. version string which
+/// parsers expect there. Other members stay sorted.
+pub fn to_jcs_vec_with_first<T: Serialize>(value: &T, first: &str) -> Result<Vec<u8>, Error> {
+    let mut value =
+        serde_json::to_value(value).map_err(|e| Error::SerializationError(Box::new(e)))?;
+    let Some(first_value) = value.as_object_mut().and_then(|map| map.remove(first)) else {
+        return to_jcs_vec(&value);
+    };
+    let mut rest = String::new();
+    write_value(&mut rest, &value)?;
+    let mut out = String::from("{");
+    write_string(&mut out, first)?;
+    out.push(':');
+    write_value(&mut out, &first_value)?;
+    if rest != "{}" {
+        out.push(',');
+    }
+    out.push_str(&rest[1..]);
+    Ok(out.into_bytes())
+}
+
+fn write_value(out: &mut String, value: &Value) -> Result<(), Error> {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => {
+            let n = n.as_f64().ok_or_else(|| {
+                Error::SerializationError(format!("{} is not representable in JCS", n).into())
+            })?;
+            out.push_str(&format_number(n));
+        }
+        Value::String(s) => write_string(out, s)?,
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(out, item)?;
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            // Properties are sorted by their UTF-16 code units.
+            let mut entries: Vec<_> = map
+                .iter()
+                .map(|(k, v)| (k.encode_utf16().collect::<Vec<u16>>(), k, v))
+                .collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            out.push('{');
+            for (i, (_, key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(out, key)?;
+                out.push(':');
+                write_value(out, value)?;
+            }
+            out.push('}');
+        }
+    };
+    Ok(())
+}
+
+/// `serde_json` string escaping already matches JCS: only `"`, `\` and control
+/// characters are escaped, using lowercase hex.
+fn write_string(out: &mut String, s: &str) -> Result<(), Error> {
+    let escaped = serde_json::to_string(s).map_err(|e| Error::SerializationError(Box::new(e)))?;
+    out.push_str(&escaped);
+    Ok(())
+}
+
+/// Format number as ECMAScript `Number.prototype.toString` does.
+fn format_number(n: f64) -> String {
+    if n == 0.0 {
+        // Covers negative zero too.
+        return "0".to_string();
+    }
+    if n < 0.0 {
+        return format!("-{}", format_number(-n));
+    }
+    // Shortest round-trip digits and decimal exponent, e.g. `1.5e-7`.
+    let exp_form = format!("{:e}", n);
+    let (mantissa, exponent) = exp_form.split_once('e').unwrap();
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let exponent: i32 = exponent.parse().unwrap();
+    let k = digits.len() as i32;
+    let n = exponent + 1;
+
+    if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat((-n) as usize), digits)
+    } else {
+        let sign = if n - 1 < 0 { '-' } else { '+' };
+        let (first, rest) = digits.split_at(1);
+        if rest.is_empty() {
+            format!("{}e{}{}", first, sign, (n - 1).abs())
+        } else {
+            format!("{}.{}e{}{}", first, rest, sign, (n - 1).abs())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_number, to_jcs_vec, to_jcs_vec_with_first};
+
+    #[test]
+    pub fn test_number_formatting() {
+        // Examples from RFC 8785, Appendix B.
+        let cases = [
+            (0.0, "0"),
+            (-0.0, "0"),
+            (5e-324, "5e-324"),
+            (1.7976931348623157e308, "1.7976931348623157e+308"),
+            (9007199254740992.0, "9007199254740992"),
+            (295147905179352830000.0, "295147905179352830000"),
+            (1e21, "1e+21"),
+            (1e20, "100000000000000000000"),
+            (0.000001, "0.000001"),
+            (1e-7, "1e-7"),
+            (333333333.3333333, "333333333.3333333"),
+            (4.5, "4.5"),
+            (-1.5, "-1.5"),
+            (0.002, "0.002"),
+            (1e30, "1e+30"),
+        ];
+        for (number, expected) in cases {
+            assert_eq!(format_number(number), expected);
+        }
+    }
+
+    #[test]
+    pub fn test_canonical_object() {
+        // Example from RFC 8785, Section 3.2.3.
+        let value: serde_json::Value = serde_json::from_str(
+            r#"{
+                "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+                "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+                "literals": [null, true, false],
+                "\u20ac": "Euro Sign",
+                "\r": "Carriage Return",
+                "\ud83d\ude00": "Emoji",
+                "\ufb33": "Hebrew Letter Dalet With Dagesh"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(to_jcs_vec(&value).unwrap()).unwrap(),
+            "{\"\\r\":\"Carriage Return\",\"literals\":[null,true,false],\
+             \"numbers\":[333333333.3333333,1e+30,4.5,0.002,1e-27],\
+             \"string\":\"\u{20ac}$\\u000f\\nA'B\\\"\\\\\\\\\\\"/\",\
+             \"\u{20ac}\":\"Euro Sign\",\"\u{1f600}\":\"Emoji\",\
+             \"\u{fb33}\":\"Hebrew Letter Dalet With Dagesh\"}"
+        );
+    }
+
+    #[test]
+    pub fn test_first_member() {
+        let value = serde_json::json!({ "b": 1, "v": "version", "a": [2, 1] });
+        assert_eq!(
+            String::from_utf8(to_jcs_vec_with_first(&value, "v").unwrap()).unwrap(),
+            r#"{"v":"version","a":[2,1],"b":1}"#
+        );
+        let value = serde_json::json!({ "v": "version" });
+        assert_eq!(
+            String::from_utf8(to_jcs_vec_with_first(&value, "v").unwrap()).unwrap(),
+            r#"{"v":"version"}"#
+        );
+    }
+}
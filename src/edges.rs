@@ -0,0 +1,371 @@
+//! Edges section (`e`) of attestation, chaining it to other attestations.
+//!
+//! Edges are a map keyed by edge name, so the same attestation can be
+//! referenced under several labels. See: [`EdgesBlock`]
+
+use std::collections::HashMap;
+
+use indexmap::IndexMap;
+use said::{
+    derivation::{HashFunction, HashFunctionCode},
+    sad::{DerivationCode, SerializationFormats, SAD},
+    SelfAddressingIdentifier,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{binding::verify_said, stream, Attestation};
+
+/// Maximum number of edges followed by [`EdgesBlock::verify_chain`].
+pub const DEFAULT_MAX_DEPTH: usize = 16;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EdgeError {
+    #[error("Attestation {0} not found")]
+    NotFound(SelfAddressingIdentifier),
+
+    #[error("Attestation {0} doesn't verify")]
+    Unverified(SelfAddressingIdentifier),
+
+    #[error("Schema mismatch: expected {expected}, got {actual}")]
+    SchemaMismatch { expected: String, actual: String },
+
+    #[error("Edge chain deeper than allowed")]
+    DepthExceeded,
+}
+
+/// Reference to another attestation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Edge {
+    /// SAID of referenced attestation.
+    #[serde(rename = "n")]
+    pub node: SelfAddressingIdentifier,
+
+    /// Schema SAID the referenced attestation is required to have.
+    #[serde(rename = "s", skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EdgesBlock {
+    #[serde(rename = "d")]
+    pub said: Option<SelfAddressingIdentifier>,
+
+    /// Edges by their name, in insertion order.
+    #[serde(flatten)]
+    pub edges: IndexMap<String, Edge>,
+}
+
+/// Borrowed counterpart of `SAD` derivation data of [`EdgesBlock`].
+#[derive(Serialize)]
+struct EdgesBlockDerivation<'a> {
+    #[serde(rename = "d")]
+    said: String,
+    #[serde(flatten)]
+    edges: &'a IndexMap<String, Edge>,
+}
+
+// Implemented by hand, as derived derivation data serializes edges as
+// positional arrays in MessagePack, while they're transmitted as named maps.
+impl SAD for EdgesBlock {
+    fn compute_digest(&mut self, code: &HashFunctionCode, format: &SerializationFormats) {
+        let derivation_data = self.derivation_data(code, format);
+        self.said = Some(HashFunction::from(code.clone()).derive(&derivation_data));
+    }
+
+    fn derivation_data(&self, code: &HashFunctionCode, format: &SerializationFormats) -> Vec<u8> {
+        let derivation = EdgesBlockDerivation {
+            said: "#".repeat(code.full_size()),
+            edges: &self.edges,
+        };
+        let mut data = Vec::new();
+        stream::serialize_into(&mut data, &derivation, format)
+            .expect("edges block is serializable");
+        data
+    }
+}
+
+impl EdgesBlock {
+    /// Block of `edges` with computed SAID. Edges are flattened next to SAID,
+    /// so edge named `d` is rejected.
+    pub fn new(
+        edges: IndexMap<String, Edge>,
+        code: &HashFunctionCode,
+        format: &SerializationFormats,
+    ) -> Result<Self, crate::Error> {
+        if edges.contains_key("d") {
+            return Err(crate::Error::ReservedKey("d".to_string()));
+        }
+        let mut block = Self { said: None, edges };
+        block.compute_digest(code, format);
+        Ok(block)
+    }
+
+    /// Check if block SAID binds to edges.
+    pub fn verify(&self, format: &SerializationFormats) -> bool {
+        verify_said(&self.said, self, format)
+    }
+
+    /// Verify chain of attestations reachable through edges, following at
+    /// most [`DEFAULT_MAX_DEPTH`] edges. Not only attestations referenced by
+    /// this block are checked, but also the ones they reference in turn. See
+    /// [`EdgesBlock::verify_chain_with_depth`].
+    pub fn verify_chain<F>(&self, lookup: F) -> Result<(), IndexMap<String, EdgeError>>
+    where
+        F: Fn(&SelfAddressingIdentifier) -> Option<Attestation>,
+    {
+        self.verify_chain_with_depth(lookup, DEFAULT_MAX_DEPTH)
+    }
+
+    /// Check every edge against attestation returned by `lookup` for its
+    /// node, and recursively edges of those attestations, following at most
+    /// `max_depth` edges from this block. Failures are reported by name of
+    /// edge of this block the failing chain starts at.
+    ///
+    /// Each attestation is looked up and verified once per traversal, however
+    /// many edges point at it, so the work is bounded by the number of
+    /// distinct attestations within `max_depth`. Edge chains can't form
+    /// cycles, as attestation SAID commits to SAIDs of attestations it
+    /// references.
+    pub fn verify_chain_with_depth<F>(
+        &self,
+        lookup: F,
+        max_depth: usize,
+    ) -> Result<(), IndexMap<String, EdgeError>>
+    where
+        F: Fn(&SelfAddressingIdentifier) -> Option<Attestation>,
+    {
+        let mut verified = HashMap::new();
+        let failures: IndexMap<_, _> = self
+            .edges
+            .iter()
+            .filter_map(|(name, edge)| {
+                edge.verify(&lookup, max_depth, &mut verified)
+                    .err()
+                    .map(|error| (name.clone(), error))
+            })
+            .collect();
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures)
+        }
+    }
+}
+
+impl Edge {
+    /// Verify referenced attestation and its own edges. `verified` holds
+    /// schema of each attestation whose chain already verified, with depth
+    /// left at that point, so it's not looked up again unless reached with
+    /// more depth left.
+    fn verify<F>(
+        &self,
+        lookup: &F,
+        depth_left: usize,
+        verified: &mut HashMap<SelfAddressingIdentifier, (usize, String)>,
+    ) -> Result<(), EdgeError>
+    where
+        F: Fn(&SelfAddressingIdentifier) -> Option<Attestation>,
+    {
+        if depth_left == 0 {
+            return Err(EdgeError::DepthExceeded);
+        }
+        let schema = match verified.get(&self.node) {
+            Some((verified_depth, schema)) if *verified_depth >= depth_left => schema.clone(),
+            _ => {
+                let node =
+                    lookup(&self.node).ok_or_else(|| EdgeError::NotFound(self.node.clone()))?;
+                if node.digest.as_ref() != Some(&self.node)
+                    || !node.verify(&SerializationFormats::JSON)
+                {
+                    return Err(EdgeError::Unverified(self.node.clone()));
+                }
+                for edge in node.edges.iter().flat_map(|edges| edges.edges.values()) {
+                    edge.verify(lookup, depth_left - 1, verified)?;
+                }
+                verified.insert(self.node.clone(), (depth_left, node.schema.clone()));
+                node.schema
+            }
+        };
+        match &self.schema {
+            Some(expected) if expected != &schema => Err(EdgeError::SchemaMismatch {
+                expected: expected.clone(),
+                actual: schema,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use indexmap::IndexMap;
+    use said::{
+        derivation::{HashFunction, HashFunctionCode},
+        sad::SerializationFormats,
+    };
+
+    use super::{Edge, EdgeError};
+    use crate::{attributes::InlineAttributes, Attestation};
+
+    #[test]
+    pub fn test_named_edges() {
+        let format = SerializationFormats::JSON;
+        let hash = HashFunction::from(HashFunctionCode::Blake3_256);
+        let schema = hash.derive(b"qualification").to_string();
+        let mut attributes = InlineAttributes::default();
+        attributes.insert("degree".to_string(), "PhD".into());
+        let qualification =
+            Attestation::new_public_untargeted("issuer", None, schema.clone(), attributes, &format);
+        let said = qualification.digest.clone().unwrap();
+
+        let edge = |schema: &str| Edge {
+            node: said.clone(),
+            schema: Some(schema.to_string()),
+        };
+        let mut edges = IndexMap::new();
+        edges.insert("supervisor".to_string(), edge(&schema));
+        edges.insert("approver".to_string(), edge(&schema));
+        edges.insert("other".to_string(), edge("other schema"));
+
+        let mut attestation = Attestation::new_public_untargeted(
+            "issuer",
+            None,
+            schema.clone(),
+            InlineAttributes::default(),
+            &format,
+        );
+        assert!(attestation.links().is_empty());
+        attestation
+            .set_edges(edges.clone(), &HashFunctionCode::Blake3_256, &format)
+            .unwrap();
+        assert!(attestation.verify(&format));
+        assert_eq!(attestation.links(), vec![said.clone()]);
+
+        // Edge named `d` would clash with block SAID.
+        let mut reserved = edges.clone();
+        reserved.insert("d".to_string(), edge(&schema));
+        assert!(matches!(
+            attestation.set_edges(reserved, &HashFunctionCode::Blake3_256, &format),
+            Err(crate::Error::ReservedKey(key)) if key == "d"
+        ));
+
+        let encoded = serde_json::to_string(&attestation).unwrap();
+        assert!(encoded.contains(&format!(
+            r#""supervisor":{{"n":"{}","s":"{}"}},"approver""#,
+            said, schema
+        )));
+        let parsed: Attestation = serde_json::from_str(&encoded).unwrap();
+        assert_eq!(parsed, attestation);
+
+        let edges = attestation.edges.unwrap();
+        let failures = edges
+            .verify_chain(|node| (node == &said).then(|| qualification.clone()))
+            .unwrap_err();
+        assert_eq!(failures.len(), 1);
+        assert!(matches!(
+            failures.get("other"),
+            Some(EdgeError::SchemaMismatch { .. })
+        ));
+
+        let failures = edges.verify_chain(|_| None).unwrap_err();
+        assert_eq!(
+            failures.keys().collect::<Vec<_>>(),
+            vec!["supervisor", "approver", "other"]
+        );
+        assert_eq!(failures["approver"], EdgeError::NotFound(said));
+    }
+
+    #[test]
+    pub fn test_chain_depth() {
+        let format = SerializationFormats::JSON;
+        let code = HashFunctionCode::Blake3_256;
+        let schema = HashFunction::from(code.clone())
+            .derive(b"schema")
+            .to_string();
+
+        // Chain of attestations, each one pointing at the previous one.
+        let mut chain = vec![Attestation::new_public_untargeted(
+            "issuer",
+            None,
+            schema.clone(),
+            InlineAttributes::default(),
+            &format,
+        )];
+        for _ in 0..3 {
+            let mut edges = IndexMap::new();
+            edges.insert(
+                "previous".to_string(),
+                Edge {
+                    node: chain.last().unwrap().digest.clone().unwrap(),
+                    schema: None,
+                },
+            );
+            let mut next = chain[0].clone();
+            next.set_edges(edges, &code, &format).unwrap();
+            chain.push(next);
+        }
+        let lookup = |said: &_| {
+            chain
+                .iter()
+                .find(|acdc| acdc.digest.as_ref() == Some(said))
+                .cloned()
+        };
+
+        let edges = chain.last().unwrap().edges.clone().unwrap();
+        assert!(edges.verify_chain(lookup).is_ok());
+        assert!(edges.verify_chain_with_depth(lookup, 3).is_ok());
+        let failures = edges.verify_chain_with_depth(lookup, 2).unwrap_err();
+        assert_eq!(failures["previous"], EdgeError::DepthExceeded);
+    }
+
+    #[test]
+    pub fn test_chain_fan_out() {
+        let format = SerializationFormats::JSON;
+        let code = HashFunctionCode::Blake3_256;
+        let schema = HashFunction::from(code.clone())
+            .derive(b"schema")
+            .to_string();
+
+        // Chain of attestations, each one pointing at the previous one by
+        // three edges.
+        let mut chain = vec![Attestation::new_public_untargeted(
+            "issuer",
+            None,
+            schema.clone(),
+            InlineAttributes::default(),
+            &format,
+        )];
+        for _ in 0..14 {
+            let previous = chain.last().unwrap().digest.clone().unwrap();
+            let edges = ["first", "second", "third"]
+                .into_iter()
+                .map(|name| {
+                    let edge = Edge {
+                        node: previous.clone(),
+                        schema: Some(schema.clone()),
+                    };
+                    (name.to_string(), edge)
+                })
+                .collect();
+            let mut next = chain[0].clone();
+            next.set_edges(edges, &code, &format).unwrap();
+            chain.push(next);
+        }
+        let lookups = Cell::new(0);
+        let lookup = |said: &_| {
+            lookups.set(lookups.get() + 1);
+            chain
+                .iter()
+                .find(|acdc| acdc.digest.as_ref() == Some(said))
+                .cloned()
+        };
+
+        let edges = chain.last().unwrap().edges.clone().unwrap();
+        assert!(edges.verify_chain(lookup).is_ok());
+        assert_eq!(lookups.get(), chain.len() - 1);
+    }
+}
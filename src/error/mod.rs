@@ -1,10 +1,156 @@
 use thiserror::Error;
 
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum Error {
-    #[error("Version error")]
+    #[error("Version error: {0}")]
     VersionError(#[from] said::error::Error),
 
-    #[error("Parse error")]
-    ParseError,
+    /// Parsing failed in one of the underlying libraries. Errors of these
+    /// libraries convert into it with `?`.
+    #[error("Parse error: {0}")]
+    ParseError(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+    /// Attributes JSON supplied by caller was rejected. Unlike
+    /// [`Error::ParseError`], used where the input is known to be attributes.
+    #[error("Invalid attributes JSON: {0}")]
+    AttributesJsonInvalid(#[source] serde_json::Error),
+
+    #[error("Attributes JSON is not an object")]
+    AttributesNotObject,
+
+    /// Attestation JSON was rejected. Unlike [`Error::ParseError`], used
+    /// where the input is known to be an attestation.
+    #[error("Invalid attestation JSON: {0}")]
+    AttestationJsonInvalid(#[source] serde_json::Error),
+
+    #[error("Invalid SAID: {0}")]
+    SaidInvalid(#[source] said::error::Error),
+
+    #[error("Invalid schema SAID: {0}")]
+    SchemaSaidInvalid(#[source] said::error::Error),
+
+    #[error("SAID not computed")]
+    MissingDigest,
+
+    #[error("SAID doesn't match content")]
+    SaidMismatch,
+
+    #[error("SAID of section {0} not computed")]
+    UncomputedSection(&'static str),
+
+    #[error("Schema requires targeted attributes block")]
+    TargetRequired,
+
+    #[error("Schema doesn't allow targeted attributes block")]
+    TargetNotAllowed,
+
+    #[error("Attestation has no salted attributes block")]
+    NoSalt,
+
+    #[error("Invalid verifiable credential: {0}")]
+    VcInvalid(String),
+
+    #[error("Missing version string")]
+    MissingVersionString,
+
+    #[error("Not an ACDC message, protocol: {0}")]
+    WrongProtocol(String),
+
+    #[error("Declared size {declared} doesn't match actual size {actual}")]
+    SizeMismatch { declared: usize, actual: usize },
+
+    #[error("Attribute {key} can't be converted to {expected}")]
+    CoercionFailed { key: String, expected: String },
+
+    #[error("Duplicate key: {0}")]
+    DuplicateKey(String),
+
+    #[error("Unknown field: {0}")]
+    UnknownField(String),
+
+    #[error("Key {0} is reserved for block field")]
+    ReservedKey(String),
+
+    #[error("Unsupported salt length: {0} bytes")]
+    UnsupportedSaltLength(usize),
+
+    /// Serialization failed in one of the underlying libraries.
+    #[error("Serialization error: {0}")]
+    SerializationError(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+    #[error("Schema resolution error: {0}")]
+    SchemaResolveError(#[from] crate::schema::ResolveError),
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::ParseError(Box::new(e))
+    }
+}
+
+impl From<serde_cbor::Error> for Error {
+    fn from(e: serde_cbor::Error) -> Self {
+        Error::ParseError(Box::new(e))
+    }
+}
+
+impl From<rmp_serde::decode::Error> for Error {
+    fn from(e: rmp_serde::decode::Error) -> Self {
+        Error::ParseError(Box::new(e))
+    }
+}
+
+#[cfg(feature = "simd-json")]
+impl From<simd_json::Error> for Error {
+    fn from(e: simd_json::Error) -> Self {
+        Error::ParseError(Box::new(e))
+    }
+}
+
+impl From<cesrox::error::Error> for Error {
+    fn from(e: cesrox::error::Error) -> Self {
+        Error::ParseError(Box::new(e))
+    }
+}
+
+impl From<said::version::error::Error> for Error {
+    fn from(e: said::version::error::Error) -> Self {
+        Error::ParseError(Box::new(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error as _;
+
+    use super::Error;
+
+    #[test]
+    pub fn test_parse_error_source() {
+        fn parse(s: &str) -> Result<serde_json::Value, Error> {
+            Ok(serde_json::from_str(s)?)
+        }
+
+        let err = parse("{").unwrap_err();
+        assert!(matches!(err, Error::ParseError(_)));
+        assert!(err
+            .to_string()
+            .starts_with("Parse error: EOF while parsing"));
+        assert!(err.source().unwrap().is::<serde_json::Error>());
+
+        fn parse_cbor(data: &[u8]) -> Result<serde_json::Value, Error> {
+            Ok(serde_cbor::from_slice(data)?)
+        }
+
+        let err = parse_cbor(&[0xff]).unwrap_err();
+        assert!(err.source().unwrap().is::<serde_cbor::Error>());
+
+        // Serialization errors keep their source too.
+        let mut map = std::collections::BTreeMap::new();
+        map.insert((1, 2), 3);
+        let err = crate::canonical::to_jcs_vec(&map).unwrap_err();
+        assert!(matches!(err, Error::SerializationError(_)));
+        assert!(err.source().unwrap().is::<serde_json::Error>());
+    }
 }
@@ -0,0 +1,5 @@
+//! Adapters for presenting attestations in other credential ecosystems.
+//!
+//! See: [`vc`]
+
+pub mod vc;
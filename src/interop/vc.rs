@@ -0,0 +1,166 @@
+//! W3C Verifiable Credentials representation of attestations.
+//!
+//! Mapping: `d` → `id`, `i` → `issuer`, `s` → `credentialSchema`, `a` →
+//! `credentialSubject`. Whole attestation is also embedded in the `acdc`
+//! extension property, so [`from_vc`] restores it exactly. See: [`to_vc`]
+
+use said::version::format::SerializationFormats;
+use serde_json::{json, Map, Value};
+
+use crate::{attributes::InlineAttributes, error::Error, Attestation, Attributes};
+
+const VC_CONTEXT: &str = "https://www.w3.org/2018/credentials/v1";
+const ID_PREFIX: &str = "urn:said:";
+const ISSUER_PREFIX: &str = "did:keri:";
+/// Extension property holding attestation in its native form.
+const EXTENSION: &str = "acdc";
+
+/// Represent attestation as W3C VC JSON-LD document.
+pub fn to_vc(att: &Attestation) -> Value {
+    let subject = credential_subject(att);
+    let mut vc = json!({
+        "@context": [VC_CONTEXT],
+        "type": ["VerifiableCredential"],
+        "issuer": format!("{}{}", ISSUER_PREFIX, att.issuer),
+        "credentialSchema": { "id": att.schema, "type": "JsonSchema" },
+        "credentialSubject": subject,
+        EXTENSION: att,
+    });
+    if let Some(digest) = &att.digest {
+        vc["id"] = format!("{}{}", ID_PREFIX, digest).into();
+    }
+    vc
+}
+
+/// `credentialSubject` of attestation: target as `id` and inline attributes.
+fn credential_subject(att: &Attestation) -> Map<String, Value> {
+    let mut subject = Map::new();
+    if let Attributes::Inline(block) = &att.attrs {
+        if let Some(target) = &block.target {
+            subject.insert("id".to_string(), target.clone().into());
+        }
+        subject.extend(block.iter().map(|(k, v)| (k.to_string(), v.clone())));
+    }
+    subject
+}
+
+/// Restore attestation from W3C VC document.
+///
+/// Documents produced by [`to_vc`] are restored from the `acdc` extension
+/// property. Embedded attestation SAID has to verify and its attributes have
+/// to agree with `credentialSubject`, otherwise VC was tampered with. Other
+/// documents are mapped best-effort to a public attestation,
+/// targeted if `credentialSubject` has an `id`, with SAIDs computed anew.
+pub fn from_vc(vc: &Value) -> Result<Attestation, Error> {
+    if let Some(native) = vc.get(EXTENSION) {
+        let att: Attestation =
+            serde_json::from_value(native.clone()).map_err(Error::AttestationJsonInvalid)?;
+        if !att.verify(&SerializationFormats::JSON) {
+            return Err(Error::VcInvalid("embedded acdc doesn't verify".to_string()));
+        }
+        if vc["credentialSubject"].as_object() != Some(&credential_subject(&att)) {
+            return Err(Error::VcInvalid(
+                "credentialSubject doesn't match embedded acdc".to_string(),
+            ));
+        }
+        return Ok(att);
+    }
+
+    let issuer = match &vc["issuer"] {
+        Value::Object(issuer) => issuer.get("id").and_then(Value::as_str),
+        issuer => issuer.as_str(),
+    }
+    .ok_or_else(|| Error::VcInvalid("missing issuer".to_string()))?;
+    let issuer = issuer.strip_prefix(ISSUER_PREFIX).unwrap_or(issuer);
+    let schema = vc["credentialSchema"]["id"]
+        .as_str()
+        .ok_or_else(|| Error::VcInvalid("missing credentialSchema id".to_string()))?;
+    let mut subject = vc["credentialSubject"]
+        .as_object()
+        .cloned()
+        .ok_or_else(|| Error::VcInvalid("credentialSubject is not an object".to_string()))?;
+    let target = subject.remove("id");
+
+    let mut attributes = InlineAttributes::default();
+    attributes.extend(subject);
+    let format = SerializationFormats::JSON;
+    Ok(match target {
+        Some(Value::String(target)) => Attestation::new_public_targeted(
+            issuer,
+            &target,
+            None,
+            schema.to_string(),
+            attributes,
+            &format,
+        ),
+        Some(_) => {
+            return Err(Error::VcInvalid(
+                "credentialSubject id is not a string".to_string(),
+            ))
+        }
+        None => Attestation::new_public_untargeted(
+            issuer,
+            None,
+            schema.to_string(),
+            attributes,
+            &format,
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use said::{
+        derivation::{HashFunction, HashFunctionCode},
+        version::format::SerializationFormats,
+    };
+
+    use super::{from_vc, to_vc};
+    use crate::{attributes::InlineAttributes, error::Error, Attestation};
+
+    #[test]
+    pub fn test_vc_round_trip() -> Result<(), Error> {
+        let mut attributes = InlineAttributes::default();
+        attributes.insert("name".to_string(), "Rex".into());
+        let schema = HashFunction::from(HashFunctionCode::Blake3_256)
+            .derive(b"schema")
+            .to_string();
+        let attestation = Attestation::new_public_targeted(
+            "issuer",
+            "owner",
+            None,
+            schema.clone(),
+            attributes,
+            &SerializationFormats::JSON,
+        );
+
+        let mut vc = to_vc(&attestation);
+        assert_eq!(
+            vc["id"],
+            format!("urn:said:{}", attestation.digest.as_ref().unwrap())
+        );
+        assert_eq!(vc["issuer"], "did:keri:issuer");
+        assert_eq!(vc["credentialSchema"]["id"], schema);
+        assert_eq!(
+            vc["credentialSubject"],
+            serde_json::json!({ "id": "owner", "name": "Rex" })
+        );
+        assert_eq!(from_vc(&vc)?, attestation);
+
+        // Tampered subject or embedded attestation is rejected.
+        let mut tampered = vc.clone();
+        tampered["credentialSubject"]["name"] = "Max".into();
+        assert!(matches!(from_vc(&tampered), Err(Error::VcInvalid(_))));
+        let mut tampered = vc.clone();
+        tampered["acdc"]["a"]["name"] = "Max".into();
+        tampered["credentialSubject"]["name"] = "Max".into();
+        assert!(matches!(from_vc(&tampered), Err(Error::VcInvalid(_))));
+
+        // Without the extension property attestation is rebuilt from VC
+        // fields, which here yields the same attestation.
+        vc.as_object_mut().unwrap().remove("acdc");
+        assert_eq!(from_vc(&vc)?, attestation);
+
+        Ok(())
+    }
+}
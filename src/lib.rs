@@ -8,12 +8,24 @@
 // #![warn(clippy::pedantic)]
 // #![warn(missing_docs)]
 
+pub mod aggregate;
 pub mod attestation;
 pub mod attributes;
 pub mod authored;
+mod binding;
+pub mod canonical;
+pub mod edges;
 pub mod error;
+pub mod interop;
+pub mod registry;
+pub mod rules;
 pub mod salt;
+pub mod schema;
+pub mod stream;
+mod strict;
+pub mod template;
 
-pub use attestation::Attestation;
+pub use attestation::{Attestation, AttestationRef};
 pub use attributes::Attributes;
 pub use authored::Authored;
+pub use error::Error;
@@ -0,0 +1,146 @@
+//! Transaction Event Log (TEL) events of ACDC registry.
+//!
+//! Issuance and revocation events which make an attestation issued under
+//! registry (`ri`) revocable. See: [`issue_event`], [`revoke_event`]
+
+use chrono::{DateTime, FixedOffset, SecondsFormat};
+use said::derivation::HashFunctionCode;
+use said::version::{format::SerializationFormats, SerializationInfo};
+use said::{sad::SAD, SelfAddressingIdentifier};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TelEventType {
+    #[serde(rename = "iss")]
+    Issuance,
+    #[serde(rename = "rev")]
+    Revocation,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, SAD)]
+#[version(protocol = "KERI", major = 1, minor = 0)]
+pub struct TelEvent {
+    #[serde(rename = "t")]
+    pub event_type: TelEventType,
+
+    /// Digest of event.
+    #[said]
+    #[serde(rename = "d")]
+    pub digest: Option<SelfAddressingIdentifier>,
+
+    /// SAID of attestation.
+    #[serde(rename = "i")]
+    pub acdc: SelfAddressingIdentifier,
+
+    /// Hex encoded sequence number of event.
+    #[serde(rename = "s")]
+    pub sn: String,
+
+    #[serde(rename = "ri")]
+    pub registry_identifier: SelfAddressingIdentifier,
+
+    /// Digest of prior event.
+    #[serde(rename = "p", skip_serializing_if = "Option::is_none")]
+    pub prior: Option<SelfAddressingIdentifier>,
+
+    /// Event datetime.
+    #[serde(rename = "dt")]
+    pub datetime: String,
+}
+
+impl TelEvent {
+    fn new(
+        event_type: TelEventType,
+        acdc: &SelfAddressingIdentifier,
+        sn: u64,
+        registry_identifier: &SelfAddressingIdentifier,
+        prior: Option<&SelfAddressingIdentifier>,
+        dt: DateTime<FixedOffset>,
+    ) -> Self {
+        let mut event = Self {
+            event_type,
+            digest: None,
+            acdc: acdc.clone(),
+            sn: format!("{:x}", sn),
+            registry_identifier: registry_identifier.clone(),
+            prior: prior.cloned(),
+            datetime: dt.to_rfc3339_opts(SecondsFormat::Micros, false),
+        };
+        // Compute digest and replace `d` field with SAID.
+        event.compute_digest(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON);
+        event
+    }
+}
+
+/// Issuance (`iss`) event of attestation `acdc_said` in registry `registry_id`.
+pub fn issue_event(
+    acdc_said: &SelfAddressingIdentifier,
+    registry_id: &SelfAddressingIdentifier,
+    dt: DateTime<FixedOffset>,
+) -> TelEvent {
+    TelEvent::new(TelEventType::Issuance, acdc_said, 0, registry_id, None, dt)
+}
+
+/// Revocation (`rev`) event of attestation `acdc_said`, anchored to the SAID of
+/// its issuance event.
+pub fn revoke_event(
+    acdc_said: &SelfAddressingIdentifier,
+    registry_id: &SelfAddressingIdentifier,
+    issuance: &SelfAddressingIdentifier,
+    dt: DateTime<FixedOffset>,
+) -> TelEvent {
+    TelEvent::new(
+        TelEventType::Revocation,
+        acdc_said,
+        1,
+        registry_id,
+        Some(issuance),
+        dt,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use said::{
+        derivation::{HashFunction, HashFunctionCode},
+        sad::{SerializationFormats, SAD},
+        version::Encode,
+    };
+
+    use super::{issue_event, revoke_event};
+
+    #[test]
+    pub fn test_issue_and_revoke() {
+        let hash = HashFunction::from(HashFunctionCode::Blake3_256);
+        let acdc = hash.derive(b"acdc");
+        let registry = hash.derive(b"registry");
+        let dt = DateTime::parse_from_rfc3339("2023-06-01T12:00:00+00:00").unwrap();
+
+        let iss = issue_event(&acdc, &registry, dt);
+        let rev = revoke_event(&acdc, &registry, iss.digest.as_ref().unwrap(), dt);
+
+        for event in [&iss, &rev] {
+            assert!(event.digest.as_ref().unwrap().verify_binding(
+                &event.derivation_data(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON)
+            ));
+        }
+
+        let encoded = String::from_utf8(
+            rev.encode(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            encoded,
+            format!(
+                r#"{{"v":"KERI10JSON{:06x}_","t":"rev","d":"{}","i":"{}","s":"1","ri":"{}","p":"{}","dt":"2023-06-01T12:00:00.000000+00:00"}}"#,
+                encoded.len(),
+                rev.digest.as_ref().unwrap(),
+                acdc,
+                registry,
+                iss.digest.as_ref().unwrap()
+            )
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! Rules section (`r`) of attestation.
+//!
+//! Like attributes, rules are either inlined or referenced by SAID of a
+//! rules block shared by many attestations. See: [`Rules`]
+
+use indexmap::IndexMap;
+use said::{
+    derivation::HashFunctionCode,
+    sad::{SerializationFormats, SAD},
+    SelfAddressingIdentifier,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{binding::verify_said, error::Error, schema::ResolveError};
+
+/// Rules (e.g. license or data agreement clauses) by their name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, SAD)]
+pub struct RulesBlock {
+    #[said]
+    #[serde(rename = "d")]
+    pub said: Option<SelfAddressingIdentifier>,
+
+    #[serde(flatten)]
+    pub rules: IndexMap<String, serde_json::Value>,
+}
+
+impl RulesBlock {
+    /// Block of `rules` with computed SAID. Rules are flattened next to SAID,
+    /// so rule named `d` is rejected.
+    pub fn new(
+        rules: IndexMap<String, serde_json::Value>,
+        code: &HashFunctionCode,
+        format: &SerializationFormats,
+    ) -> Result<Self, Error> {
+        if rules.contains_key("d") {
+            return Err(Error::ReservedKey("d".to_string()));
+        }
+        let mut block = Self { said: None, rules };
+        block.compute_digest(code, format);
+        Ok(block)
+    }
+
+    /// Check if block SAID binds to rules.
+    pub fn verify(&self, format: &SerializationFormats) -> bool {
+        verify_said(&self.said, self, format)
+    }
+}
+
+/// Attestation rules.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Rules {
+    /// Inlined rules block.
+    Inline(RulesBlock),
+    /// External rules block identified by its [`SelfAddressingIdentifier`].
+    External(SelfAddressingIdentifier),
+}
+
+/// Source of shared rules blocks identified by their SAID.
+pub trait RulesResolver {
+    fn resolve(&self, said: &SelfAddressingIdentifier) -> Result<RulesBlock, ResolveError>;
+}
+
+impl Rules {
+    /// SAID of rules block, inline or referenced.
+    pub fn said(&self) -> Option<&SelfAddressingIdentifier> {
+        match self {
+            Rules::Inline(block) => block.said.as_ref(),
+            Rules::External(said) => Some(said),
+        }
+    }
+
+    /// Get rules block, fetching external one from `resolver`. Fetched block
+    /// must be the one attestation committed to, i.e. its SAID has to bind
+    /// and match the reference.
+    pub fn resolve<R: RulesResolver>(&self, resolver: &R) -> Result<RulesBlock, ResolveError> {
+        let said = match self {
+            Rules::Inline(block) => return Ok(block.clone()),
+            Rules::External(said) => said,
+        };
+        let block = resolver.resolve(said)?;
+        match &block.said {
+            Some(computed) if computed == said && block.verify(&SerializationFormats::JSON) => {
+                Ok(block)
+            }
+            _ => {
+                let mut computed = block.clone();
+                computed
+                    .compute_digest(&said.derivation.clone().into(), &SerializationFormats::JSON);
+                Err(ResolveError::SaidMismatch {
+                    expected: said.clone(),
+                    computed: computed.said.unwrap(),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+    use said::{derivation::HashFunctionCode, sad::SerializationFormats, SelfAddressingIdentifier};
+
+    use super::{Rules, RulesBlock, RulesResolver};
+    use crate::schema::ResolveError;
+
+    struct StaticResolver(RulesBlock);
+
+    impl RulesResolver for StaticResolver {
+        fn resolve(&self, _said: &SelfAddressingIdentifier) -> Result<RulesBlock, ResolveError> {
+            Ok(self.0.clone())
+        }
+    }
+
+    fn block(license: &str) -> RulesBlock {
+        let mut rules = IndexMap::new();
+        rules.insert("license".to_string(), license.into());
+        RulesBlock::new(
+            rules,
+            &HashFunctionCode::Blake3_256,
+            &SerializationFormats::JSON,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    pub fn test_resolve_external_rules() -> Result<(), ResolveError> {
+        let shared = block("CC-BY-4.0");
+        let external = Rules::External(shared.said.clone().unwrap());
+        assert_eq!(
+            serde_json::to_string(&external).unwrap(),
+            format!(r#""{}""#, shared.said.as_ref().unwrap())
+        );
+
+        assert_eq!(external.resolve(&StaticResolver(shared.clone()))?, shared);
+        assert_eq!(
+            Rules::Inline(shared.clone()).resolve(&StaticResolver(block("other")))?,
+            shared
+        );
+
+        assert!(matches!(
+            external.resolve(&StaticResolver(block("CC0"))),
+            Err(ResolveError::SaidMismatch { .. })
+        ));
+        Ok(())
+    }
+}
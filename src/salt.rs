@@ -1,22 +1,139 @@
-use cesrox::primitives::{codes::serial_number::SerialNumberCode, CesrPrimitive};
-use uuid::Uuid;
+use cesrox::{
+    conversion::from_bytes_to_text,
+    primitives::{
+        codes::{seed::SeedCode, PrimitiveCode},
+        CesrPrimitive,
+    },
+};
+use rand::RngCore;
+
+use crate::error::Error;
+
+/// CESR code of 256 bit salt (`Salt_256`), which `cesrox` doesn't define.
+const SALT_256_CODE: &str = "a";
 
 pub struct Salt {
-    uuid: Uuid,
+    derivative: Vec<u8>,
 }
 
 pub fn new_uuid() -> String {
-    let uuid = uuid::Uuid::new_v4();
-    let salt = Salt { uuid };
+    new_uuid_from_rng(&mut rand::thread_rng())
+}
+
+/// UUID v4 salt drawn from given `rng`, e.g. seeded one in tests or hardware
+/// CSPRNG.
+pub fn new_uuid_from_rng(rng: &mut impl RngCore) -> String {
+    let mut bytes = [0u8; 16];
+    rng.fill_bytes(&mut bytes);
+    let uuid = uuid::Builder::from_random_bytes(bytes).into_uuid();
+    let salt = Salt {
+        derivative: uuid.as_bytes().to_vec(),
+    };
     salt.to_str()
 }
 
+/// Generate CESR encoded random salt of `bytes` length. Supported lengths are
+/// 16 bytes (`0A`, same code as [`new_uuid`]) and 32 bytes (`a`).
+pub fn new_salt(bytes: usize) -> Result<String, Error> {
+    new_salt_from_rng(bytes, &mut rand::thread_rng())
+}
+
+/// [`new_salt`] drawn from given `rng`.
+pub fn new_salt_from_rng(bytes: usize, rng: &mut impl RngCore) -> Result<String, Error> {
+    if !matches!(bytes, 16 | 32) {
+        return Err(Error::UnsupportedSaltLength(bytes));
+    }
+    let mut derivative = vec![0u8; bytes];
+    rng.fill_bytes(&mut derivative);
+    if bytes == 32 {
+        // Code replaces the lead byte, as `CesrPrimitive::to_str` does.
+        let text = from_bytes_to_text(&derivative);
+        return Ok(format!("{}{}", SALT_256_CODE, &text[SALT_256_CODE.len()..]));
+    }
+    Ok(Salt { derivative }.to_str())
+}
+
+/// Length in bytes of salt generated by this module, recognized by its code
+/// and text length.
+pub(crate) fn salt_len(salt: &str) -> Option<usize> {
+    match (salt.get(..2)?, salt.len()) {
+        ("0A", 24) => Some(16),
+        (code, 44) if code.starts_with(SALT_256_CODE) => Some(32),
+        _ => None,
+    }
+}
+
 impl CesrPrimitive for Salt {
     fn derivative(&self) -> Vec<u8> {
-        self.uuid.as_bytes().to_vec()
+        self.derivative.clone()
+    }
+
+    fn derivation_code(&self) -> PrimitiveCode {
+        PrimitiveCode::Seed(SeedCode::RandomSeed128)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use cesrox::{conversion::from_text_to_bytes, primitives::codes::seed::SeedCode};
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::{new_salt, new_salt_from_rng, new_uuid, new_uuid_from_rng};
+    use crate::error::Error;
+
+    #[test]
+    pub fn test_salt_length() -> Result<(), Error> {
+        let uuid = new_uuid();
+        assert_eq!(uuid.len(), 24);
+        assert!(uuid.starts_with("0A"));
+
+        let salt = new_salt(16)?;
+        assert_eq!(salt.len(), 24);
+        assert!(salt.starts_with("0A"));
+
+        let salt = new_salt(32)?;
+        assert_eq!(salt.len(), 44);
+        assert!(salt.starts_with('a'));
+
+        assert!(matches!(
+            new_salt(20),
+            Err(Error::UnsupportedSaltLength(20))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_uuid_from_seeded_rng() {
+        let salt = new_uuid_from_rng(&mut StdRng::seed_from_u64(7));
+        assert_eq!(salt, new_uuid_from_rng(&mut StdRng::seed_from_u64(7)));
+        assert_ne!(salt, new_uuid_from_rng(&mut StdRng::seed_from_u64(8)));
+        assert!(salt.starts_with("0A"));
+
+        let salt = new_salt_from_rng(16, &mut StdRng::seed_from_u64(7)).unwrap();
+        assert_eq!(
+            salt,
+            new_salt_from_rng(16, &mut StdRng::seed_from_u64(7)).unwrap()
+        );
+        assert!(salt.starts_with("0A"));
+    }
+
+    #[test]
+    pub fn test_salt_code() {
+        let salt = new_uuid_from_rng(&mut StdRng::seed_from_u64(7));
+        assert_eq!(SeedCode::from_str(&salt).unwrap(), SeedCode::RandomSeed128);
+
+        let mut bytes = [0u8; 16];
+        StdRng::seed_from_u64(7).fill_bytes(&mut bytes);
+        let uuid = uuid::Builder::from_random_bytes(bytes).into_uuid();
+        let decoded = from_text_to_bytes(&salt.as_bytes()[2..]).unwrap();
+        assert_eq!(&decoded[2..], uuid.as_bytes());
 
-    fn derivation_code(&self) -> cesrox::primitives::codes::PrimitiveCode {
-        cesrox::primitives::codes::PrimitiveCode::SerialNumber(SerialNumberCode)
+        let salt = new_salt_from_rng(32, &mut StdRng::seed_from_u64(7)).unwrap();
+        let mut bytes = [0u8; 32];
+        StdRng::seed_from_u64(7).fill_bytes(&mut bytes);
+        let decoded = from_text_to_bytes(&salt.as_bytes()[1..]).unwrap();
+        assert_eq!(&decoded[1..], bytes);
     }
 }
@@ -0,0 +1,214 @@
+//! Schema resolution.
+//!
+//! See: [`SchemaResolver`], [`CachingSchemaResolver`]
+
+use std::{collections::HashMap, sync::Mutex};
+
+use indexmap::IndexMap;
+use said::{
+    derivation::{HashFunction, HashFunctionCode},
+    sad::DerivationCode,
+    SelfAddressingIdentifier,
+};
+use serde_json::value::RawValue;
+use thiserror::Error;
+
+/// Field of schema document which contains its SAID.
+const SCHEMA_SAID_FIELD: &str = "$id";
+
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum ResolveError {
+    #[error("Schema {0} not found")]
+    NotFound(SelfAddressingIdentifier),
+
+    #[error("Schema SAID mismatch: expected {expected}, computed {computed}")]
+    SaidMismatch {
+        expected: SelfAddressingIdentifier,
+        computed: SelfAddressingIdentifier,
+    },
+
+    #[error("Invalid schema document: {0}")]
+    InvalidSchema(String),
+
+    #[error("Resolver error: {0}")]
+    Resolver(String),
+}
+
+/// Source of schema documents identified by their SAID.
+pub trait SchemaResolver {
+    /// Get serialized schema document.
+    fn resolve(&self, said: &SelfAddressingIdentifier) -> Result<Vec<u8>, ResolveError>;
+}
+
+/// Storage of already resolved schema documents.
+pub trait SchemaStore {
+    fn get(&self, said: &SelfAddressingIdentifier) -> Option<Vec<u8>>;
+    fn put(&self, said: SelfAddressingIdentifier, schema: Vec<u8>);
+}
+
+/// In-memory [`SchemaStore`].
+#[derive(Default)]
+pub struct InMemorySchemaStore(Mutex<HashMap<SelfAddressingIdentifier, Vec<u8>>>);
+
+impl SchemaStore for InMemorySchemaStore {
+    fn get(&self, said: &SelfAddressingIdentifier) -> Option<Vec<u8>> {
+        self.0.lock().unwrap().get(said).cloned()
+    }
+
+    fn put(&self, said: SelfAddressingIdentifier, schema: Vec<u8>) {
+        self.0.lock().unwrap().insert(said, schema);
+    }
+}
+
+/// [`SchemaResolver`] which caches documents fetched by the inner resolver.
+///
+/// Fetched document is trusted and cached only if its computed SAID matches
+/// the requested one, so a compromised source can't substitute a schema other
+/// than the one credential committed to.
+pub struct CachingSchemaResolver<R: SchemaResolver, S: SchemaStore> {
+    inner: R,
+    store: S,
+}
+
+impl<R: SchemaResolver, S: SchemaStore> CachingSchemaResolver<R, S> {
+    pub fn new(inner: R, store: S) -> Self {
+        Self { inner, store }
+    }
+}
+
+impl<R: SchemaResolver, S: SchemaStore> SchemaResolver for CachingSchemaResolver<R, S> {
+    fn resolve(&self, said: &SelfAddressingIdentifier) -> Result<Vec<u8>, ResolveError> {
+        if let Some(schema) = self.store.get(said) {
+            return Ok(schema);
+        }
+        let schema = self.inner.resolve(said)?;
+        let computed = compute_schema_said(&schema, &said.derivation.clone().into())?;
+        if &computed != said {
+            return Err(ResolveError::SaidMismatch {
+                expected: said.clone(),
+                computed,
+            });
+        }
+        self.store.put(said.clone(), schema.clone());
+        Ok(schema)
+    }
+}
+
+/// Compute SAID of JSON schema document.
+///
+/// Digest is computed over compact serialization of the document with `$id`
+/// field replaced by dummy string, keeping original field order.
+pub fn compute_schema_said(
+    schema: &[u8],
+    code: &HashFunctionCode,
+) -> Result<SelfAddressingIdentifier, ResolveError> {
+    let compact = compact_json(schema)?;
+    let mut document: IndexMap<String, Box<RawValue>> =
+        serde_json::from_str(&compact).map_err(|e| ResolveError::InvalidSchema(e.to_string()))?;
+    let id = document
+        .get_mut(SCHEMA_SAID_FIELD)
+        .ok_or_else(|| ResolveError::InvalidSchema(format!("missing {}", SCHEMA_SAID_FIELD)))?;
+    *id = RawValue::from_string(format!("\"{}\"", "#".repeat(code.full_size())))
+        .map_err(|e| ResolveError::InvalidSchema(e.to_string()))?;
+    let derivation_data =
+        serde_json::to_vec(&document).map_err(|e| ResolveError::InvalidSchema(e.to_string()))?;
+    Ok(HashFunction::from(code.clone()).derive(&derivation_data))
+}
+
+/// Strip insignificant whitespace from JSON text.
+fn compact_json(json: &[u8]) -> Result<String, ResolveError> {
+    let json = std::str::from_utf8(json).map_err(|e| ResolveError::InvalidSchema(e.to_string()))?;
+    let mut compact = String::with_capacity(json.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in json.chars() {
+        if in_string {
+            compact.push(c);
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => (),
+            }
+        } else if !c.is_ascii_whitespace() {
+            in_string = c == '"';
+            compact.push(c);
+        }
+    }
+    Ok(compact)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use said::{derivation::HashFunctionCode, SelfAddressingIdentifier};
+
+    use super::*;
+
+    struct StaticResolver {
+        schema: Vec<u8>,
+        calls: Cell<usize>,
+    }
+
+    impl SchemaResolver for StaticResolver {
+        fn resolve(&self, _said: &SelfAddressingIdentifier) -> Result<Vec<u8>, ResolveError> {
+            self.calls.set(self.calls.get() + 1);
+            Ok(self.schema.clone())
+        }
+    }
+
+    fn schema(title: &str) -> (SelfAddressingIdentifier, Vec<u8>) {
+        let template = format!(
+            r#"{{
+                "$id": "",
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "title": "{}",
+                "type": "object",
+                "properties": {{ "a": {{ "type": "object" }} }}
+            }}"#,
+            title
+        );
+        let said = compute_schema_said(template.as_bytes(), &HashFunctionCode::Blake3_256).unwrap();
+        let schema = template.replacen(r#""$id": """#, &format!(r#""$id": "{}""#, said), 1);
+        (said, schema.into_bytes())
+    }
+
+    #[test]
+    pub fn test_caching_resolver() -> Result<(), ResolveError> {
+        let (said, document) = schema("Pet");
+        let resolver = CachingSchemaResolver::new(
+            StaticResolver {
+                schema: document.clone(),
+                calls: Cell::new(0),
+            },
+            InMemorySchemaStore::default(),
+        );
+
+        assert_eq!(resolver.resolve(&said)?, document);
+        assert_eq!(resolver.resolve(&said)?, document);
+        assert_eq!(resolver.inner.calls.get(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_said_mismatch() {
+        let (said, _) = schema("Pet");
+        let (_, other_document) = schema("Pet owner");
+        let resolver = CachingSchemaResolver::new(
+            StaticResolver {
+                schema: other_document,
+                calls: Cell::new(0),
+            },
+            InMemorySchemaStore::default(),
+        );
+
+        assert!(matches!(
+            resolver.resolve(&said),
+            Err(ResolveError::SaidMismatch { .. })
+        ));
+        assert!(resolver.store.get(&said).is_none());
+    }
+}
@@ -0,0 +1,185 @@
+//! Incremental SAID computation.
+//!
+//! See: [`DigestWriter`]
+
+use std::io::{self, Write};
+
+use blake2::Blake2b;
+use said::{
+    derivation::{HashFunction, HashFunctionCode},
+    version::format::SerializationFormats,
+    SelfAddressingIdentifier,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256, Sha512};
+use sha3::{Sha3_256, Sha3_512};
+
+use crate::error::Error;
+
+enum HasherState {
+    Blake3_256(blake3::Hasher),
+    Blake3_512(blake3::Hasher),
+    Blake2B512(Blake2b),
+    Sha3_256(Sha3_256),
+    Sha3_512(Sha3_512),
+    Sha2_256(Sha256),
+    Sha2_512(Sha512),
+    /// Keyed Blake2 variants are not exposed incrementally by `said`, so the
+    /// data is buffered and hashed in one shot to keep digests identical.
+    Buffered(Vec<u8>),
+}
+
+/// [`Write`] adapter which feeds written bytes directly into a hasher.
+///
+/// Serializing into a `DigestWriter` produces the same SAID as hashing the
+/// fully serialized bytes, without keeping the serialization in memory.
+pub struct DigestWriter {
+    code: HashFunctionCode,
+    state: HasherState,
+}
+
+impl DigestWriter {
+    pub fn new(code: &HashFunctionCode) -> Self {
+        let state = match code {
+            HashFunctionCode::Blake3_256 => HasherState::Blake3_256(blake3::Hasher::new()),
+            HashFunctionCode::Blake3_512 => HasherState::Blake3_512(blake3::Hasher::new()),
+            HashFunctionCode::Blake2B512 => HasherState::Blake2B512(Blake2b::new()),
+            HashFunctionCode::SHA3_256 => HasherState::Sha3_256(Sha3_256::new()),
+            HashFunctionCode::SHA3_512 => HasherState::Sha3_512(Sha3_512::new()),
+            HashFunctionCode::SHA2_256 => HasherState::Sha2_256(Sha256::new()),
+            HashFunctionCode::SHA2_512 => HasherState::Sha2_512(Sha512::new()),
+            HashFunctionCode::Blake2B256(_) | HashFunctionCode::Blake2S256(_) => {
+                HasherState::Buffered(Vec::new())
+            }
+        };
+        Self {
+            code: code.clone(),
+            state,
+        }
+    }
+
+    /// Consume the writer and return SAID of all written data.
+    pub fn finalize(self) -> SelfAddressingIdentifier {
+        let hash_function = HashFunction::from(self.code);
+        let digest = match self.state {
+            HasherState::Blake3_256(hasher) => hasher.finalize().as_bytes().to_vec(),
+            HasherState::Blake3_512(hasher) => {
+                let mut out = [0u8; 64];
+                hasher.finalize_xof().fill(&mut out);
+                out.to_vec()
+            }
+            HasherState::Blake2B512(hasher) => hasher.finalize().to_vec(),
+            HasherState::Sha3_256(hasher) => hasher.finalize().to_vec(),
+            HasherState::Sha3_512(hasher) => hasher.finalize().to_vec(),
+            HasherState::Sha2_256(hasher) => hasher.finalize().to_vec(),
+            HasherState::Sha2_512(hasher) => hasher.finalize().to_vec(),
+            HasherState::Buffered(data) => hash_function.digest(&data),
+        };
+        SelfAddressingIdentifier::new(hash_function, digest)
+    }
+}
+
+impl Write for DigestWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.state {
+            HasherState::Blake3_256(hasher) | HasherState::Blake3_512(hasher) => {
+                hasher.update(buf);
+            }
+            HasherState::Blake2B512(hasher) => hasher.update(buf),
+            HasherState::Sha3_256(hasher) => hasher.update(buf),
+            HasherState::Sha3_512(hasher) => hasher.update(buf),
+            HasherState::Sha2_256(hasher) => hasher.update(buf),
+            HasherState::Sha2_512(hasher) => hasher.update(buf),
+            HasherState::Buffered(data) => data.extend_from_slice(buf),
+        };
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// [`Write`] adapter which only counts written bytes.
+#[derive(Default)]
+pub(crate) struct CountingWriter(pub usize);
+
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Serialize `value` in given format directly into `writer`.
+pub(crate) fn serialize_into<W: Write, T: Serialize>(
+    writer: &mut W,
+    value: &T,
+    format: &SerializationFormats,
+) -> Result<(), Error> {
+    match format {
+        SerializationFormats::JSON => {
+            serde_json::to_writer(writer, value).map_err(|e| Error::SerializationError(Box::new(e)))
+        }
+        SerializationFormats::CBOR => {
+            serde_cbor::to_writer(writer, value).map_err(|e| Error::SerializationError(Box::new(e)))
+        }
+        SerializationFormats::MGPK => rmp_serde::encode::write_named(writer, value)
+            .map_err(|e| Error::SerializationError(Box::new(e))),
+    }
+}
+
+/// Length of `value` serialized in given format.
+pub(crate) fn serialized_len<T: Serialize>(
+    value: &T,
+    format: &SerializationFormats,
+) -> Result<usize, Error> {
+    let mut counter = CountingWriter::default();
+    serialize_into(&mut counter, value, format)?;
+    Ok(counter.0)
+}
+
+/// Compute SAID of `value` serialized in given format, without holding the
+/// serialization in memory.
+pub fn derive<T: Serialize>(
+    value: &T,
+    code: &HashFunctionCode,
+    format: &SerializationFormats,
+) -> Result<SelfAddressingIdentifier, Error> {
+    let mut writer = DigestWriter::new(code);
+    serialize_into(&mut writer, value, format)?;
+    Ok(writer.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use said::derivation::{HashFunction, HashFunctionCode};
+
+    use super::DigestWriter;
+
+    #[test]
+    pub fn test_chunked_digest() {
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        for code in [
+            HashFunctionCode::Blake3_256,
+            HashFunctionCode::Blake3_512,
+            HashFunctionCode::Blake2B512,
+            HashFunctionCode::SHA3_256,
+            HashFunctionCode::SHA3_512,
+            HashFunctionCode::SHA2_256,
+            HashFunctionCode::SHA2_512,
+        ] {
+            let mut writer = DigestWriter::new(&code);
+            for chunk in data.chunks(4096) {
+                writer.write_all(chunk).unwrap();
+            }
+            assert_eq!(writer.finalize(), HashFunction::from(code).derive(&data));
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Checks for strict attestation parsing.
+
+use std::{collections::HashSet, fmt};
+
+use serde::{
+    de::{MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer,
+};
+
+/// First object key which appears more than once within a single object,
+/// anywhere in deserialized document.
+///
+/// `serde_json` silently keeps the last value of a duplicated key, so the same
+/// parsed data can have different wire representations.
+pub(crate) struct FirstDuplicateKey(pub Option<String>);
+
+impl<'de> Deserialize<'de> for FirstDuplicateKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(DuplicateKeyVisitor)
+    }
+}
+
+struct DuplicateKeyVisitor;
+
+impl<'de> Visitor<'de> for DuplicateKeyVisitor {
+    type Value = FirstDuplicateKey;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any JSON value")
+    }
+
+    fn visit_bool<E>(self, _v: bool) -> Result<Self::Value, E> {
+        Ok(FirstDuplicateKey(None))
+    }
+
+    fn visit_i64<E>(self, _v: i64) -> Result<Self::Value, E> {
+        Ok(FirstDuplicateKey(None))
+    }
+
+    fn visit_u64<E>(self, _v: u64) -> Result<Self::Value, E> {
+        Ok(FirstDuplicateKey(None))
+    }
+
+    fn visit_f64<E>(self, _v: f64) -> Result<Self::Value, E> {
+        Ok(FirstDuplicateKey(None))
+    }
+
+    fn visit_str<E>(self, _v: &str) -> Result<Self::Value, E> {
+        Ok(FirstDuplicateKey(None))
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(FirstDuplicateKey(None))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut duplicate = None;
+        while let Some(FirstDuplicateKey(inner)) = seq.next_element()? {
+            duplicate = duplicate.or(inner);
+        }
+        Ok(FirstDuplicateKey(duplicate))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut keys = HashSet::new();
+        let mut duplicate = None;
+        while let Some(key) = map.next_key::<String>()? {
+            let FirstDuplicateKey(inner) = map.next_value()?;
+            if duplicate.is_none() {
+                duplicate = if keys.contains(&key) {
+                    Some(key)
+                } else {
+                    keys.insert(key);
+                    inner
+                };
+            }
+        }
+        Ok(FirstDuplicateKey(duplicate))
+    }
+}
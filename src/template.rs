@@ -0,0 +1,157 @@
+//! Bulk issuance of attestations sharing everything but attributes.
+//!
+//! See: [`AttestationTemplate`]
+
+use std::io::Write;
+
+use said::{
+    derivation::HashFunctionCode,
+    sad::DerivationCode,
+    version::{format::SerializationFormats, SerializationInfo},
+    SelfAddressingIdentifier,
+};
+use serde::Serialize;
+
+use crate::{
+    attestation::{registry_identifier, PROTOCOL, VERSION_STRING_LEN},
+    error::Error,
+    stream::DigestWriter,
+    Attestation, Attributes,
+};
+
+/// Fields of attestation derivation data which follow the version string.
+#[derive(Serialize)]
+struct InvariantFields<'a> {
+    #[serde(rename = "d")]
+    digest: String,
+    #[serde(rename = "i")]
+    issuer: &'a str,
+    #[serde(rename = "ri", with = "registry_identifier")]
+    registry_identifier: &'a Option<SelfAddressingIdentifier>,
+    #[serde(rename = "s")]
+    schema: &'a str,
+}
+
+/// Issuer of JSON attestations which differ only in attributes.
+///
+/// Part of derivation data which doesn't depend on attributes is serialized
+/// once, so issuing an attestation only serializes and hashes its attributes
+/// block. Resulting SAIDs are the same as the ones set by
+/// [`SAD::compute_digest`](said::sad::SAD::compute_digest).
+pub struct AttestationTemplate {
+    issuer: String,
+    registry_identifier: Option<SelfAddressingIdentifier>,
+    schema: String,
+    code: HashFunctionCode,
+    /// Serialized invariant fields, from `,"d":` up to `"a":`.
+    invariant: Vec<u8>,
+}
+
+impl AttestationTemplate {
+    pub fn new(
+        issuer: &str,
+        registry_identifier: Option<SelfAddressingIdentifier>,
+        schema: String,
+        code: &HashFunctionCode,
+    ) -> Result<Self, Error> {
+        let fields = InvariantFields {
+            digest: "#".repeat(code.full_size()),
+            issuer,
+            registry_identifier: &registry_identifier,
+            schema: &schema,
+        };
+        let mut invariant =
+            serde_json::to_vec(&fields).map_err(|e| Error::SerializationError(Box::new(e)))?;
+        // Turn `{...}` into `,...,"a":`.
+        invariant[0] = b',';
+        invariant.pop();
+        invariant.extend_from_slice(br#","a":"#);
+        Ok(Self {
+            issuer: issuer.to_string(),
+            registry_identifier,
+            schema,
+            code: code.clone(),
+            invariant,
+        })
+    }
+
+    /// Build attestation with given attributes and compute its SAID.
+    pub fn issue(&self, attrs: Attributes) -> Result<Attestation, Error> {
+        let attrs_json =
+            serde_json::to_vec(&attrs).map_err(|e| Error::SerializationError(Box::new(e)))?;
+        let size = br#"{"v":""#.len()
+            + VERSION_STRING_LEN
+            + 1
+            + self.invariant.len()
+            + attrs_json.len()
+            + 1;
+        let version =
+            SerializationInfo::new(PROTOCOL.to_string(), 1, 0, SerializationFormats::JSON, size)
+                .to_str();
+
+        let mut writer = DigestWriter::new(&self.code);
+        for chunk in [
+            br#"{"v":""#.as_slice(),
+            version.as_bytes(),
+            b"\"",
+            &self.invariant,
+            &attrs_json,
+            b"}",
+        ] {
+            writer
+                .write_all(chunk)
+                .map_err(|e| Error::SerializationError(Box::new(e)))?;
+        }
+
+        Ok(Attestation {
+            digest: Some(writer.finalize()),
+            issuer: self.issuer.clone(),
+            registry_identifier: self.registry_identifier.clone(),
+            schema: self.schema.clone(),
+            attrs,
+            edges: None,
+            rules: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use said::{
+        derivation::{HashFunction, HashFunctionCode},
+        version::format::SerializationFormats,
+    };
+
+    use super::AttestationTemplate;
+    use crate::{attributes::InlineAttributes, error::Error, Attestation};
+
+    #[test]
+    pub fn test_template_matches_naive_digest() -> Result<(), Error> {
+        let hash = HashFunction::from(HashFunctionCode::Blake3_256);
+        let schema = hash.derive(b"schema").to_string();
+        let format = SerializationFormats::JSON;
+
+        for registry in [None, Some(hash.derive(b"registry"))] {
+            let template = AttestationTemplate::new(
+                "issuer",
+                registry.clone(),
+                schema.clone(),
+                &HashFunctionCode::Blake3_256,
+            )?;
+            for i in 0..8 {
+                let mut attributes = InlineAttributes::default();
+                attributes.insert("n".to_string(), "x".repeat(i * 50).into());
+                let naive = Attestation::new_public_untargeted(
+                    "issuer",
+                    registry.clone(),
+                    schema.clone(),
+                    attributes.clone(),
+                    &format,
+                );
+                let issued = template.issue(attributes.to_untargeted_public_block(&format))?;
+                assert_eq!(issued, naive);
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,4 +1,9 @@
-use acdc::{attributes::InlineAttributes, error::Error, Attestation};
+use acdc::{
+    attributes::{AttributesBlock, InlineAttributes, SaidBlock},
+    error::Error,
+    rules::{Rules, RulesBlock},
+    Attestation,
+};
 use said::{
     derivation::{HashFunction, HashFunctionCode},
     sad::{SerializationFormats, SAD},
@@ -13,11 +18,12 @@ pub fn test_new_targeted_public_attestation() -> Result<(), Error> {
     let attestation = Attestation::new_public_targeted(
         "issuer",
         "target",
-        "".to_string(),
+        None,
         HashFunction::from(HashFunctionCode::Blake3_256)
             .derive(&[0; 30])
             .to_string(),
         attributes,
+        &SerializationFormats::JSON,
     );
 
     let digest = attestation.digest.clone().unwrap();
@@ -44,11 +50,12 @@ pub fn test_new_untargeted_public_attestation() -> Result<(), Error> {
 
     let attestation = Attestation::new_public_untargeted(
         "issuer",
-        "".to_string(),
+        None,
         HashFunction::from(HashFunctionCode::Blake3_256)
             .derive(&[0; 30])
             .to_string(),
         attributes,
+        &SerializationFormats::JSON,
     );
 
     let digest = attestation.digest.clone().unwrap();
@@ -75,11 +82,12 @@ pub fn test_new_untargeted_private_attestation() -> Result<(), Error> {
 
     let attestation = Attestation::new_private_untargeted(
         "issuer",
-        "".to_string(),
+        None,
         HashFunction::from(HashFunctionCode::Blake3_256)
             .derive(&[0; 30])
             .to_string(),
         attributes,
+        &SerializationFormats::JSON,
     );
 
     let digest = attestation.digest.clone().unwrap();
@@ -122,11 +130,12 @@ pub fn test_new_targeted_private_attestation() -> Result<(), Error> {
     let attestation = Attestation::new_private_targeted(
         "issuer",
         "target",
-        "".to_string(),
+        None,
         HashFunction::from(HashFunctionCode::Blake3_256)
             .derive(&[0; 30])
             .to_string(),
         attributes,
+        &SerializationFormats::JSON,
     );
 
     let digest = attestation.digest.clone().unwrap();
@@ -145,3 +154,943 @@ pub fn test_new_targeted_private_attestation() -> Result<(), Error> {
 
     Ok(())
 }
+
+#[test]
+pub fn test_merge_attributes() -> Result<(), Error> {
+    let mut base = InlineAttributes::default();
+    base.insert("name".to_string(), "Hella".into());
+    base.insert("species".to_string(), "cat".into());
+
+    let mut request = InlineAttributes::default();
+    request.insert("health".to_string(), "great".into());
+    request.insert("name".to_string(), "Mruczek".into());
+
+    base.merge(request);
+    base.extend([("age".to_string(), 3.into())]);
+
+    let expected: InlineAttributes =
+        r#"{"name":"Mruczek","species":"cat","health":"great","age":3}"#.parse()?;
+    assert_eq!(base, expected);
+    assert_eq!(
+        serde_json::to_string(&base).unwrap(),
+        serde_json::to_string(&expected).unwrap()
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn test_parse_attributes_errors() {
+    assert!(matches!(
+        r#"{"name":"Hella""#.parse::<InlineAttributes>(),
+        Err(Error::AttributesJsonInvalid(_))
+    ));
+    assert!(matches!(
+        r#"["name","Hella"]"#.parse::<InlineAttributes>(),
+        Err(Error::AttributesNotObject)
+    ));
+}
+
+#[test]
+pub fn test_attributes_accessors() -> Result<(), Error> {
+    let attributes: InlineAttributes = r#"{"name":"Hella","species":"cat"}"#.parse()?;
+    let block = match attributes.to_untargeted_public_block(&SerializationFormats::JSON) {
+        acdc::Attributes::Inline(block) => block,
+        _ => unreachable!(),
+    };
+
+    assert_eq!(block.len(), 2);
+    assert!(!block.is_empty());
+    assert_eq!(block.get("species"), Some(&"cat".into()));
+    assert_eq!(block.get("age"), None);
+    assert_eq!(
+        block.iter().map(|(k, _)| k).collect::<Vec<_>>(),
+        vec!["name", "species"]
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn test_cbor_attestation_bindings() -> Result<(), Error> {
+    let mut attributes = InlineAttributes::default();
+    attributes.insert("greetings".to_string(), "Hello".into());
+
+    let attestation = Attestation::new_private_targeted(
+        "issuer",
+        "target",
+        None,
+        HashFunction::from(HashFunctionCode::Blake3_256)
+            .derive(&[0; 30])
+            .to_string(),
+        attributes,
+        &SerializationFormats::CBOR,
+    );
+
+    let digest = attestation.digest.clone().unwrap();
+    assert!(digest.verify_binding(
+        &attestation.derivation_data(&HashFunctionCode::Blake3_256, &SerializationFormats::CBOR)
+    ));
+
+    let block = match &attestation.attrs {
+        acdc::Attributes::Inline(block) => block,
+        _ => unreachable!(),
+    };
+    let block_digest = block.said.clone().unwrap();
+    assert!(block_digest.verify_binding(
+        &block.derivation_data(&HashFunctionCode::Blake3_256, &SerializationFormats::CBOR)
+    ));
+    assert!(!block_digest.verify_binding(
+        &block.derivation_data(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON)
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_attestations_order() -> Result<(), Error> {
+    let issue = |dt: Option<&str>, name: &str| {
+        let mut attributes = InlineAttributes::default();
+        if let Some(dt) = dt {
+            attributes.insert("dt".to_string(), dt.into());
+        }
+        attributes.insert("name".to_string(), name.into());
+        Attestation::new_public_untargeted(
+            "issuer",
+            None,
+            HashFunction::from(HashFunctionCode::Blake3_256)
+                .derive(&[0; 30])
+                .to_string(),
+            attributes,
+            &SerializationFormats::JSON,
+        )
+    };
+
+    // 10:00 UTC, issued before `second` despite its later local time.
+    let first = issue(Some("2023-06-01T12:00:00+02:00"), "first");
+    let second = issue(Some("2023-06-01T11:00:00+00:00"), "second");
+    let undated_a = issue(None, "a");
+    let undated_b = issue(None, "b");
+
+    let wallet: std::collections::BTreeSet<_> = [
+        undated_a.clone(),
+        second.clone(),
+        undated_b.clone(),
+        first.clone(),
+    ]
+    .into_iter()
+    .collect();
+    let (first_undated, second_undated) = if undated_a.digest.as_ref().unwrap().to_string()
+        < undated_b.digest.as_ref().unwrap().to_string()
+    {
+        (undated_a, undated_b)
+    } else {
+        (undated_b, undated_a)
+    };
+    assert_eq!(
+        wallet.into_iter().collect::<Vec<_>>(),
+        vec![first, second, first_undated, second_undated]
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn test_set_attributes() -> Result<(), Error> {
+    let mut attributes = InlineAttributes::default();
+    attributes.insert("greetings".to_string(), "Hello".into());
+
+    let mut attestation = Attestation::new_private_targeted(
+        "issuer",
+        "target",
+        None,
+        HashFunction::from(HashFunctionCode::Blake3_256)
+            .derive(&[0; 30])
+            .to_string(),
+        attributes,
+        &SerializationFormats::JSON,
+    );
+    let old_digest = attestation.digest.clone();
+
+    let mut attributes = InlineAttributes::default();
+    attributes.insert("greetings".to_string(), "Goodbye".into());
+    attestation.set_attributes(
+        attributes,
+        &HashFunctionCode::Blake3_256,
+        &SerializationFormats::JSON,
+    );
+
+    let digest = attestation.digest.clone().unwrap();
+    assert_ne!(Some(&digest), old_digest.as_ref());
+    assert!(digest.verify_binding(
+        &attestation.derivation_data(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON)
+    ));
+    match &attestation.attrs {
+        acdc::Attributes::Inline(block) => {
+            assert_eq!(block.target.as_deref(), Some("target"));
+            assert!(block.uuid.is_some());
+            assert_eq!(block.get("greetings"), Some(&"Goodbye".into()));
+            assert!(block.said.as_ref().unwrap().verify_binding(
+                &block.derivation_data(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON)
+            ));
+        }
+        _ => unreachable!(),
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn test_nested_said_block() -> Result<(), Error> {
+    let code = HashFunctionCode::Blake3_256;
+    let format = SerializationFormats::JSON;
+
+    let mut address = InlineAttributes::default();
+    address.insert("street".to_string(), "Main".into());
+    address.insert("city".to_string(), "Geneva".into());
+    let address = SaidBlock::new(address, &code, &format)?;
+
+    let mut attributes = InlineAttributes::default();
+    attributes.insert("name".to_string(), "Hella".into());
+    attributes.insert_said_block("address".to_string(), address.clone())?;
+
+    let block = match attributes.to_untargeted_public_block(&format) {
+        acdc::Attributes::Inline(block) => block,
+        _ => unreachable!(),
+    };
+    let encoded = serde_json::to_string(&block).unwrap();
+    assert!(encoded.contains(&format!(
+        r#""address":{{"d":"{}","street":"Main","city":"Geneva"}}"#,
+        address.said.as_ref().unwrap()
+    )));
+
+    // Both levels bind after round trip.
+    let parsed: AttributesBlock = serde_json::from_str(&encoded).unwrap();
+    assert!(parsed
+        .said
+        .as_ref()
+        .unwrap()
+        .verify_binding(&parsed.derivation_data(&code, &format)));
+    let nested = parsed.data.said_block("address").unwrap();
+    assert_eq!(nested, address);
+    assert!(nested.verify(&format));
+
+    // Tampering with nested block breaks its binding.
+    let mut tampered = nested;
+    tampered.data.insert("city".to_string(), "Bern".into());
+    assert!(!tampered.verify(&format));
+
+    // `d` attribute would clash with block SAID.
+    let mut reserved = InlineAttributes::default();
+    reserved.insert("d".to_string(), "Geneva".into());
+    assert!(matches!(
+        SaidBlock::new(reserved, &code, &format),
+        Err(Error::ReservedKey(key)) if key == "d"
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_new_metadata_attestation() -> Result<(), Error> {
+    let mut attributes = InlineAttributes::default();
+    attributes.insert("greetings".to_string(), "Hello".into());
+    let schema = HashFunction::from(HashFunctionCode::Blake3_256)
+        .derive(&[0; 30])
+        .to_string();
+
+    let full = Attestation::new_private_untargeted(
+        "issuer",
+        None,
+        schema.clone(),
+        attributes,
+        &SerializationFormats::JSON,
+    );
+    let metadata = Attestation::new_metadata(
+        "issuer",
+        None,
+        schema,
+        &full.attrs,
+        &SerializationFormats::JSON,
+    );
+
+    let block_said = match &full.attrs {
+        acdc::Attributes::Inline(block) => block.said.clone().unwrap(),
+        _ => unreachable!(),
+    };
+    assert_eq!(
+        metadata.attrs,
+        acdc::Attributes::External(block_said.clone())
+    );
+    let encoded = String::from_utf8(
+        metadata
+            .encode(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON)
+            .unwrap(),
+    )
+    .unwrap();
+    assert!(encoded.ends_with(&format!(r#""a":"{}"}}"#, block_said)));
+    // Only attributes SAID is carried.
+    assert!(metadata.edges.is_none() && metadata.rules.is_none());
+
+    let digest = metadata.digest.clone().unwrap();
+    assert_ne!(Some(&digest), full.digest.as_ref());
+    assert!(digest.verify_binding(
+        &metadata.derivation_data(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON)
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_new_empty_attestation() -> Result<(), Error> {
+    let schema = HashFunction::from(HashFunctionCode::Blake3_256)
+        .derive(&[0; 30])
+        .to_string();
+    let attestation =
+        Attestation::new_empty("issuer", None, schema.clone(), &SerializationFormats::JSON);
+    assert_eq!(
+        attestation,
+        Attestation::new_empty("issuer", None, schema, &SerializationFormats::JSON)
+    );
+
+    let encoded = attestation
+        .encode(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON)
+        .unwrap();
+    assert!(String::from_utf8(encoded.clone())
+        .unwrap()
+        .ends_with(r#","a":{}}}"#));
+
+    let parsed: Attestation = serde_json::from_slice(&encoded).unwrap();
+    assert_eq!(parsed, attestation);
+    assert!(parsed.digest.as_ref().unwrap().verify_binding(
+        &parsed.derivation_data(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON)
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_section_saids() -> Result<(), Error> {
+    let mut attributes = InlineAttributes::default();
+    attributes.insert("greetings".to_string(), "Hello".into());
+    let schema = HashFunction::from(HashFunctionCode::Blake3_256)
+        .derive(&[0; 30])
+        .to_string();
+
+    let full = Attestation::new_public_untargeted(
+        "issuer",
+        None,
+        schema.clone(),
+        attributes,
+        &SerializationFormats::JSON,
+    );
+    let compact = Attestation::new_metadata(
+        "issuer",
+        None,
+        schema,
+        &full.attrs,
+        &SerializationFormats::JSON,
+    );
+
+    let saids = full.section_saids();
+    assert_eq!(saids.keys().collect::<Vec<_>>(), vec![&"a"]);
+    assert_eq!(saids, compact.section_saids());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_embedded_acdc() -> Result<(), Error> {
+    let format = SerializationFormats::JSON;
+    let schema = HashFunction::from(HashFunctionCode::Blake3_256)
+        .derive(&[0; 30])
+        .to_string();
+
+    let mut child_attributes = InlineAttributes::default();
+    child_attributes.insert("name".to_string(), "Rex".into());
+    let child = Attestation::new_public_untargeted(
+        "issuer",
+        None,
+        schema.clone(),
+        child_attributes,
+        &format,
+    );
+    let mut tampered = serde_json::to_value(&child).unwrap();
+    tampered["a"]["a"]["name"] = "Max".into();
+
+    let mut attributes = InlineAttributes::default();
+    attributes.insert("pet".to_string(), serde_json::to_value(&child).unwrap());
+    attributes.insert("tampered".to_string(), tampered);
+    attributes.insert("name".to_string(), "Hella".into());
+    let parent = Attestation::new_public_untargeted("issuer", None, schema, attributes, &format);
+
+    let block = match &parent.attrs {
+        acdc::Attributes::Inline(block) => block,
+        _ => unreachable!(),
+    };
+    assert_eq!(block.embedded_acdc("pet"), Some(child));
+    assert_eq!(block.embedded_acdc("tampered"), None);
+    assert_eq!(block.embedded_acdc("name"), None);
+    assert_eq!(block.embedded_acdc("missing"), None);
+    assert!(parent.verify(&format));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_externalize_attributes() -> Result<(), Error> {
+    let mut attributes = InlineAttributes::default();
+    attributes.insert("greetings".to_string(), "Hello".into());
+    let mut block = match attributes.to_untargeted_public_block(&SerializationFormats::JSON) {
+        acdc::Attributes::Inline(block) => block,
+        _ => unreachable!(),
+    };
+
+    assert_eq!(
+        block.externalize()?,
+        acdc::Attributes::External(block.said.clone().unwrap())
+    );
+
+    block.said = None;
+    assert!(matches!(block.externalize(), Err(Error::MissingDigest)));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_new_compact_attestation() -> Result<(), Error> {
+    let hash = HashFunction::from(HashFunctionCode::Blake3_256);
+    let attrs_said = hash.derive(b"attributes");
+    let schema = hash.derive(&[0; 30]).to_string();
+
+    let attestation = Attestation::new_compact(
+        "issuer",
+        None,
+        schema.clone(),
+        attrs_said.clone(),
+        &SerializationFormats::JSON,
+    );
+    let encoded = String::from_utf8(
+        attestation
+            .encode(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON)
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        encoded,
+        format!(
+            r#"{{"v":"ACDC10JSON{:06x}_","d":"{}","i":"issuer","ri":"","s":"{}","a":"{}"}}"#,
+            encoded.len(),
+            attestation.digest.as_ref().unwrap(),
+            schema,
+            attrs_said
+        )
+    );
+    assert!(attestation.verify(&SerializationFormats::JSON));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_canonical_bytes() -> Result<(), Error> {
+    let mut attributes = InlineAttributes::default();
+    attributes.insert("greetings".to_string(), "Hello".into());
+    let code = HashFunctionCode::Blake3_256;
+
+    for format in [
+        SerializationFormats::JSON,
+        SerializationFormats::CBOR,
+        SerializationFormats::MGPK,
+    ] {
+        let attestation = Attestation::new_public_untargeted(
+            "issuer",
+            None,
+            HashFunction::from(code.clone())
+                .derive(&[0; 30])
+                .to_string(),
+            attributes.clone(),
+            &format,
+        );
+        let encoded = attestation.canonical_bytes(&code, &format)?;
+        assert_eq!(attestation.encode(&code, &format).unwrap(), encoded);
+        let mut written = Vec::new();
+        attestation.encode_to(&mut written, &code, &format)?;
+        assert_eq!(written, encoded);
+        assert_eq!(attestation.signing_payload(&code, &format)?, encoded);
+
+        // SAID binds to the encoded bytes with dummy digest.
+        let mut undigested = attestation.clone();
+        undigested.digest = None;
+        let derivation_data = undigested.canonical_bytes(&code, &format)?;
+        assert_eq!(attestation.derivation_data(&code, &format), derivation_data);
+        assert!(attestation
+            .digest
+            .as_ref()
+            .unwrap()
+            .verify_binding(&derivation_data));
+        assert_eq!(derivation_data.len(), encoded.len());
+        assert!(matches!(
+            undigested.signing_payload(&code, &format),
+            Err(Error::MissingDigest)
+        ));
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn test_rotate_salt() -> Result<(), Error> {
+    let code = HashFunctionCode::Blake3_256;
+    let format = SerializationFormats::JSON;
+    let mut attributes = InlineAttributes::default();
+    attributes.insert("greetings".to_string(), "Hello".into());
+    let schema = HashFunction::from(code.clone())
+        .derive(&[0; 30])
+        .to_string();
+
+    let private = Attestation::new_private_targeted(
+        "issuer",
+        "target",
+        None,
+        schema.clone(),
+        attributes.clone(),
+        &format,
+    );
+    let rotated = private.rotate_salt(&code, &format)?;
+    assert_ne!(rotated.digest, private.digest);
+    assert!(rotated.verify(&format));
+    match (&private.attrs, &rotated.attrs) {
+        (acdc::Attributes::Inline(original), acdc::Attributes::Inline(block)) => {
+            assert_ne!(block.uuid, original.uuid);
+            assert_ne!(block.said, original.said);
+            assert_eq!(block.target, original.target);
+            assert_eq!(block.data, original.data);
+        }
+        _ => unreachable!(),
+    }
+
+    // Longer salt isn't downgraded.
+    let mut long_salt = Attestation::new_public_untargeted(
+        "issuer",
+        None,
+        schema.clone(),
+        attributes.clone(),
+        &format,
+    );
+    long_salt.attrs = attributes
+        .clone()
+        .to_untargeted_private_block_with_salt(acdc::salt::new_salt(32)?, &format);
+    long_salt.compute_digest(&code, &format);
+    let rotated = long_salt.rotate_salt(&code, &format)?;
+    match (&long_salt.attrs, &rotated.attrs) {
+        (acdc::Attributes::Inline(original), acdc::Attributes::Inline(block)) => {
+            let (original, new) = (
+                original.uuid.as_ref().unwrap(),
+                block.uuid.as_ref().unwrap(),
+            );
+            assert_ne!(new, original);
+            assert_eq!(new.len(), 44);
+            assert!(new.starts_with('a'));
+        }
+        _ => unreachable!(),
+    }
+
+    let public = Attestation::new_public_untargeted("issuer", None, schema, attributes, &format);
+    assert!(matches!(
+        public.rotate_salt(&code, &format),
+        Err(Error::NoSalt)
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_validate_targeting() -> Result<(), Error> {
+    let format = SerializationFormats::JSON;
+    let mut attributes = InlineAttributes::default();
+    attributes.insert("name".to_string(), "Rex".into());
+    let schema_said = HashFunction::from(HashFunctionCode::Blake3_256)
+        .derive(&[0; 30])
+        .to_string();
+    let targeted = Attestation::new_public_targeted(
+        "issuer",
+        "owner",
+        None,
+        schema_said.clone(),
+        attributes.clone(),
+        &format,
+    );
+    let untargeted =
+        Attestation::new_public_untargeted("issuer", None, schema_said, attributes, &format);
+
+    let schema = |block: serde_json::Value| {
+        serde_json::json!({
+            "properties": { "a": { "oneOf": [{ "type": "string" }, block] } }
+        })
+    };
+    let targeted_schema = schema(serde_json::json!({
+        "type": "object",
+        "properties": { "d": {}, "i": {}, "a": {} },
+        "required": ["d", "i", "a"]
+    }));
+    let untargeted_schema = schema(serde_json::json!({
+        "type": "object",
+        "properties": { "d": {}, "a": {} },
+        "additionalProperties": false,
+        "required": ["d", "a"]
+    }));
+    let open_schema = schema(serde_json::json!({ "type": "object" }));
+
+    targeted.validate_targeting(&targeted_schema)?;
+    untargeted.validate_targeting(&untargeted_schema)?;
+    targeted.validate_targeting(&open_schema)?;
+    untargeted.validate_targeting(&open_schema)?;
+    assert!(matches!(
+        untargeted.validate_targeting(&targeted_schema),
+        Err(Error::TargetRequired)
+    ));
+    assert!(matches!(
+        targeted.validate_targeting(&untargeted_schema),
+        Err(Error::TargetNotAllowed)
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_inline_and_external_rules() -> Result<(), Error> {
+    let code = HashFunctionCode::Blake3_256;
+    let format = SerializationFormats::JSON;
+    let mut rules = indexmap::IndexMap::new();
+    rules.insert("license".to_string(), "CC-BY-4.0".into());
+    let block = RulesBlock::new(rules.clone(), &code, &format)?;
+
+    // Rule named `d` would clash with block SAID.
+    let mut reserved = rules;
+    reserved.insert("d".to_string(), "CC0".into());
+    assert!(matches!(
+        RulesBlock::new(reserved, &code, &format),
+        Err(Error::ReservedKey(key)) if key == "d"
+    ));
+
+    let mut attestation = Attestation::new_public_untargeted(
+        "issuer",
+        None,
+        HashFunction::from(code.clone())
+            .derive(&[0; 30])
+            .to_string(),
+        InlineAttributes::default(),
+        &format,
+    );
+    let mut compact = attestation.clone();
+    attestation.set_rules(Rules::Inline(block.clone()), &code, &format);
+    compact.set_rules(Rules::External(block.said.clone().unwrap()), &code, &format);
+
+    for acdc in [&attestation, &compact] {
+        assert!(acdc.verify(&format));
+        assert_eq!(acdc.section_saids().get("r"), block.said.as_ref());
+        let encoded = serde_json::to_string(acdc).unwrap();
+        assert_eq!(
+            &serde_json::from_str::<Attestation>(&encoded).unwrap(),
+            acdc
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn test_attributes_data() -> Result<(), Error> {
+    let mut attributes = InlineAttributes::default();
+    attributes.insert("greetings".to_string(), "Hello".into());
+    let private = attributes
+        .clone()
+        .to_untargeted_private_block(&SerializationFormats::JSON);
+
+    let data = private.data().unwrap();
+    assert_eq!(data, &attributes);
+    assert_eq!(
+        serde_json::to_string(data).unwrap(),
+        r#"{"greetings":"Hello"}"#
+    );
+
+    let said = HashFunction::from(HashFunctionCode::Blake3_256).derive(b"attributes");
+    assert_eq!(acdc::Attributes::External(said).data(), None);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_redacted_attributes() -> Result<(), Error> {
+    let attributes: InlineAttributes =
+        r#"{"name":"Hella","age":42,"address":{"city":"Bern"},"tags":["a",true]}"#.parse()?;
+    let block = match attributes
+        .to_targeted_private_block("target".to_string(), &SerializationFormats::JSON)
+    {
+        acdc::Attributes::Inline(block) => block,
+        _ => unreachable!(),
+    };
+
+    assert_eq!(
+        block.redacted(),
+        serde_json::json!({
+            "d": block.said.as_ref().unwrap().to_string(),
+            "i": "target",
+            "u": "*".repeat(24),
+            "a": {
+                "name": "*****",
+                "age": "**",
+                "address": { "city": "****" },
+                "tags": ["*", "****"]
+            }
+        })
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn test_default_attestation() -> Result<(), Error> {
+    let default = Attestation::default();
+    assert!(default.digest.is_some());
+    assert!(default.verify(&SerializationFormats::JSON));
+    assert_eq!(default.attrs.data(), Some(&InlineAttributes::default()));
+
+    let mut attestation = Attestation {
+        issuer: "issuer".to_string(),
+        ..Default::default()
+    };
+    let mut attributes = InlineAttributes::default();
+    attributes.insert("greetings".to_string(), "Hello".into());
+    attestation.set_attributes(
+        attributes,
+        &HashFunctionCode::Blake3_256,
+        &SerializationFormats::JSON,
+    );
+    assert!(attestation.verify(&SerializationFormats::JSON));
+    assert_ne!(attestation.digest, default.digest);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_uncomputed_sections() -> Result<(), Error> {
+    let attestation = Attestation::default();
+    attestation.ensure_sections_computed()?;
+
+    let mut uncomputed = attestation.clone();
+    if let acdc::Attributes::Inline(block) = &mut uncomputed.attrs {
+        block.said = None;
+    }
+    assert!(matches!(
+        uncomputed.ensure_sections_computed(),
+        Err(Error::UncomputedSection("a"))
+    ));
+
+    let mut uncomputed = attestation;
+    uncomputed.rules = Some(Rules::Inline(RulesBlock::default()));
+    assert!(matches!(
+        uncomputed.ensure_sections_computed(),
+        Err(Error::UncomputedSection("r"))
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_coerce_to_schema() -> Result<(), Error> {
+    let schema = serde_json::json!({
+        "type": "object",
+        "properties": {
+            "age": { "type": "integer" },
+            "height": { "type": "number" },
+            "member": { "type": "boolean" },
+            "nickname": { "type": ["string", "null"] },
+            "score": { "type": ["integer", "null"] }
+        }
+    });
+
+    let mut attributes: InlineAttributes = r#"{"age":"30","height":"1.85","member":"true",
+        "nickname":"42","score":"null","note":"7"}"#
+        .parse()?;
+    attributes.coerce_to_schema(&schema)?;
+    let expected: InlineAttributes = r#"{"age":30,"height":1.85,"member":true,
+        "nickname":"42","score":null,"note":"7"}"#
+        .parse()?;
+    assert_eq!(attributes, expected);
+
+    let mut invalid: InlineAttributes = r#"{"age":"thirty"}"#.parse()?;
+    assert!(matches!(
+        invalid.coerce_to_schema(&schema),
+        Err(Error::CoercionFailed { key, .. }) if key == "age"
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_scalar_attributes() -> Result<(), Error> {
+    let format = SerializationFormats::JSON;
+    let hash = HashFunction::from(HashFunctionCode::Blake3_256);
+    let schema = hash.derive(&[0; 30]).to_string();
+    let value = serde_json::Value::from("0123456789abcdef");
+
+    let attestation =
+        Attestation::new_scalar("issuer", None, schema.clone(), value.clone(), &format);
+    assert!(attestation.verify(&format));
+    assert!(attestation.section_saids().is_empty());
+
+    let encoded = attestation
+        .encode(&HashFunctionCode::Blake3_256, &format)
+        .unwrap();
+    assert!(String::from_utf8(encoded.clone())
+        .unwrap()
+        .ends_with(r#""a":"0123456789abcdef"}"#));
+    let parsed = Attestation::parse(&encoded)?;
+    assert_eq!(parsed.attrs, acdc::Attributes::Scalar(value));
+    assert_eq!(parsed, attestation);
+
+    // Scalar is already most compact form.
+    let metadata =
+        Attestation::new_metadata("issuer", None, schema.clone(), &attestation.attrs, &format);
+    assert_eq!(metadata, attestation);
+
+    // Malformed attributes block is an error, not a scalar object.
+    let malformed = String::from_utf8(encoded)
+        .unwrap()
+        .replace(r#""a":"0123456789abcdef""#, r#""a":{"d":"garbage","a":{}}"#);
+    assert!(serde_json::from_str::<Attestation>(&malformed).is_err());
+
+    // Scalar string which is a valid SAID makes external attributes, as it
+    // reads back as such.
+    let said = hash.derive(b"attributes");
+    let scalar = Attestation::new_scalar(
+        "issuer",
+        None,
+        schema.clone(),
+        said.to_string().into(),
+        &format,
+    );
+    let external = Attestation::new_compact("issuer", None, schema, said, &format);
+    assert_eq!(scalar, external);
+    assert_eq!(
+        serde_json::from_str::<Attestation>(&serde_json::to_string(&scalar).unwrap()).unwrap(),
+        external
+    );
+
+    Ok(())
+}
+
+#[test]
+pub fn test_with_registry() {
+    let format = SerializationFormats::JSON;
+    let hash = HashFunction::from(HashFunctionCode::Blake3_256);
+    let registry = hash.derive(b"registry");
+    let schema = hash.derive(&[0; 30]).to_string();
+    let mut data = InlineAttributes::default();
+    data.insert("greetings".to_string(), "Hello".into());
+
+    let with_registry =
+        Attestation::new_public_untargeted("issuer", None, schema.clone(), data.clone(), &format)
+            .with_registry(registry.clone(), &HashFunctionCode::Blake3_256, &format);
+    let expected =
+        Attestation::new_public_untargeted("issuer", Some(registry), schema, data, &format);
+
+    assert_eq!(with_registry, expected);
+    assert!(with_registry.verify(&format));
+}
+
+#[test]
+pub fn test_attributes_block_from_verified() {
+    let hash = HashFunction::from(HashFunctionCode::Blake3_256);
+    let said = hash.derive(b"attributes").to_string();
+    let mut block: AttributesBlock = serde_json::from_value(serde_json::json!({
+        "u": "0AHcgNghkDaG7OY1wjaDAE0q",
+        "a": { "temp": 45, "lat": 46.9 }
+    }))
+    .unwrap();
+    block.compute_digest(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON);
+    let mut value = serde_json::to_value(&block).unwrap();
+
+    let verified = AttributesBlock::from_verified(value.clone()).unwrap();
+    assert_eq!(verified, block);
+
+    value["a"]["temp"] = 46.into();
+    assert!(matches!(
+        AttributesBlock::from_verified(value.clone()),
+        Err(Error::SaidMismatch)
+    ));
+
+    value["d"] = said.into();
+    assert!(matches!(
+        AttributesBlock::from_verified(value.clone()),
+        Err(Error::SaidMismatch)
+    ));
+
+    value.as_object_mut().unwrap().remove("d");
+    assert!(matches!(
+        AttributesBlock::from_verified(value),
+        Err(Error::MissingDigest)
+    ));
+}
+
+#[test]
+pub fn test_nested_object_order_pinned() {
+    // Keys of nested objects keep insertion order (`preserve_order` feature of
+    // `serde_json`). Sorting them would change the SAID.
+    let format = SerializationFormats::JSON;
+    let mut attributes = InlineAttributes::default();
+    attributes.insert(
+        "address".to_string(),
+        serde_json::json!({ "street": "Main", "city": "Geneva" }),
+    );
+    let schema = HashFunction::from(HashFunctionCode::Blake3_256)
+        .derive(&[0; 30])
+        .to_string();
+    let attestation =
+        Attestation::new_public_untargeted("issuer", None, schema, attributes, &format);
+
+    let encoded = String::from_utf8(
+        attestation
+            .encode(&HashFunctionCode::Blake3_256, &format)
+            .unwrap(),
+    )
+    .unwrap();
+    assert!(encoded.contains(r#""address":{"street":"Main","city":"Geneva"}"#));
+    assert_eq!(
+        attestation.digest.unwrap().to_string(),
+        "EAOfzr592t1SxIVE1hHC66CVsEstHe4MUiER2khhxj5M"
+    );
+}
+
+#[test]
+pub fn test_float_roundtrip_pinned() {
+    // Without the `float_roundtrip` feature of `serde_json` this value parses
+    // to a neighbouring float, and re-encoding changes the SAID.
+    let format = SerializationFormats::JSON;
+    let value: serde_json::Value = serde_json::from_str("1.0000000000076929e-1").unwrap();
+    assert_eq!(value.as_f64().unwrap(), 1.0000000000076929e-1);
+
+    let mut attributes = InlineAttributes::default();
+    attributes.insert("ratio".to_string(), value);
+    let schema = HashFunction::from(HashFunctionCode::Blake3_256)
+        .derive(&[0; 30])
+        .to_string();
+    let attestation =
+        Attestation::new_public_untargeted("issuer", None, schema, attributes, &format);
+    let encoded = attestation
+        .encode(&HashFunctionCode::Blake3_256, &format)
+        .unwrap();
+
+    let parsed = Attestation::parse(&encoded).unwrap();
+    assert_eq!(
+        parsed
+            .encode(&HashFunctionCode::Blake3_256, &format)
+            .unwrap(),
+        encoded
+    );
+    assert_eq!(
+        attestation.digest.unwrap().to_string(),
+        "EDQ9N2zA_g-HADT4Mbjb4LzAfuNeiiSkQWgrQLUgxIh8"
+    );
+}
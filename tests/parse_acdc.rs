@@ -0,0 +1,243 @@
+use acdc::{attributes::InlineAttributes, error::Error, Attestation, AttestationRef};
+use said::{
+    derivation::{HashFunction, HashFunctionCode},
+    sad::SerializationFormats,
+    version::Encode,
+};
+
+fn attestation() -> Attestation {
+    let mut attributes = InlineAttributes::default();
+    attributes.insert("x".to_string(), 1.into());
+
+    Attestation::new_public_untargeted(
+        "issuer",
+        None,
+        HashFunction::from(HashFunctionCode::Blake3_256)
+            .derive(&[0; 30])
+            .to_string(),
+        attributes,
+        &SerializationFormats::JSON,
+    )
+}
+
+#[test]
+pub fn test_parse_strict() -> Result<(), Error> {
+    let attestation = attestation();
+    let encoded = String::from_utf8(
+        attestation
+            .encode(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON)
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(Attestation::parse_strict(&encoded)?, attestation);
+
+    let duplicated = encoded.replace(r#"{"x":1}"#, r#"{"x":1,"x":2}"#);
+    assert_ne!(duplicated, encoded);
+    assert!(serde_json::from_str::<Attestation>(&duplicated).is_ok());
+    assert!(matches!(
+        Attestation::parse_strict(&duplicated),
+        Err(Error::DuplicateKey(key)) if key == "x"
+    ));
+
+    let smuggled = encoded.replacen(r#","d":"#, r#","x":"smuggled","d":"#, 1);
+    assert_ne!(smuggled, encoded);
+    assert!(serde_json::from_str::<Attestation>(&smuggled).is_ok());
+    assert!(matches!(
+        Attestation::parse_strict(&smuggled),
+        Err(Error::UnknownField(key)) if key == "x"
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_registry_identifier() -> Result<(), Error> {
+    let attestation = attestation();
+    let encoded = attestation
+        .encode(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON)
+        .unwrap();
+    assert!(String::from_utf8(encoded).unwrap().contains(r#""ri":"","#));
+
+    let registry = HashFunction::from(HashFunctionCode::Blake3_256).derive(b"registry");
+    let mut attestation = attestation;
+    attestation.registry_identifier = Some(registry.clone());
+    let encoded = attestation
+        .encode(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON)
+        .unwrap();
+    let parsed: Attestation = serde_json::from_slice(&encoded).unwrap();
+    assert_eq!(parsed.registry_identifier, Some(registry));
+
+    let invalid = String::from_utf8(encoded)
+        .unwrap()
+        .replace(r#""ri":"E"#, r#""ri":"not a said"#);
+    assert!(serde_json::from_str::<Attestation>(&invalid).is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn test_schema_said() -> Result<(), Error> {
+    let mut attestation = attestation();
+    assert_eq!(
+        attestation.schema_said()?,
+        HashFunction::from(HashFunctionCode::Blake3_256).derive(&[0; 30])
+    );
+
+    let other = self::attestation();
+    assert_eq!(
+        attestation.schema_family(),
+        Some(attestation.schema_said()?)
+    );
+    assert!(attestation.schema_matches(&other));
+
+    attestation.schema = "not a said".to_string();
+    assert!(matches!(
+        attestation.schema_said(),
+        Err(Error::SchemaSaidInvalid(_))
+    ));
+    assert_eq!(attestation.schema_family(), None);
+    assert!(!attestation.schema_matches(&other));
+    assert!(!attestation.schema_matches(&attestation));
+
+    Ok(())
+}
+
+#[test]
+pub fn test_borrowed_attestation() -> Result<(), Error> {
+    let attestation = attestation();
+    let encoded = String::from_utf8(
+        attestation
+            .encode(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON)
+            .unwrap(),
+    )
+    .unwrap();
+
+    let borrowed: AttestationRef = serde_json::from_str(&encoded).unwrap();
+    let input = encoded.as_bytes().as_ptr_range();
+    assert!(input.contains(&borrowed.issuer.as_ptr()));
+    assert!(input.contains(&borrowed.schema.as_ptr()));
+    assert_eq!(borrowed.issuer, "issuer");
+    assert_eq!(borrowed.registry_identifier, "");
+
+    assert_eq!(borrowed.to_owned()?, attestation);
+
+    Ok(())
+}
+
+#[test]
+pub fn test_resolution_reference() -> Result<(), Error> {
+    let attestation = attestation();
+    let reference = attestation.resolution_reference()?;
+    assert_eq!(
+        serde_json::to_value(&reference).unwrap(),
+        serde_json::json!({
+            "id": attestation.digest.as_ref().unwrap().to_string(),
+            "issuer": "did:keri:issuer",
+            "schema": attestation.schema,
+        })
+    );
+
+    let mut undigested = attestation;
+    undigested.digest = None;
+    assert!(matches!(
+        undigested.resolution_reference(),
+        Err(Error::MissingDigest)
+    ));
+    Ok(())
+}
+
+#[test]
+pub fn test_parse_declared_size() -> Result<(), Error> {
+    let attestation = attestation();
+    for format in [
+        SerializationFormats::JSON,
+        SerializationFormats::CBOR,
+        SerializationFormats::MGPK,
+    ] {
+        let encoded = attestation
+            .encode(&HashFunctionCode::Blake3_256, &format)
+            .unwrap();
+        assert_eq!(Attestation::parse(&encoded)?, attestation);
+        if format == SerializationFormats::MGPK {
+            // Named map, starting with `v` key.
+            assert_eq!(encoded[0] & 0xf0, 0x80);
+            assert_eq!(encoded[1..3], [0xa1, b'v']);
+        }
+
+        let truncated = &encoded[..encoded.len() - 1];
+        assert!(matches!(
+            Attestation::parse(truncated),
+            Err(Error::SizeMismatch { declared, actual })
+                if declared == encoded.len() && actual == truncated.len()
+        ));
+    }
+
+    // Invalid JSON reports the same error with and without `simd-json`.
+    let encoded = String::from_utf8(
+        attestation
+            .encode(&HashFunctionCode::Blake3_256, &SerializationFormats::JSON)
+            .unwrap(),
+    )
+    .unwrap();
+    let mistyped = encoded.replace(r#""i":"issuer""#, r#""i":12345678"#);
+    assert_eq!(mistyped.len(), encoded.len());
+    assert!(matches!(
+        Attestation::parse(mistyped.as_bytes()),
+        Err(Error::AttestationJsonInvalid(_))
+    ));
+    let malformed = encoded.replacen(r#"",""#, r#"" ""#, 1);
+    assert!(matches!(
+        Attestation::parse(malformed.as_bytes()),
+        Err(Error::AttestationJsonInvalid(_))
+    ));
+
+    assert!(matches!(
+        Attestation::parse(br#"{"d":"","i":"issuer"}"#),
+        Err(Error::MissingVersionString)
+    ));
+
+    let event = br#"{"v":"KERI10JSON000031_","t":"icp","d":"","i":""}"#;
+    assert!(matches!(
+        Attestation::parse(event),
+        Err(Error::WrongProtocol(protocol)) if protocol == "KERI"
+    ));
+
+    // Multibyte characters within version string window.
+    for data in [
+        "{\"v\":\"AAé0JSON000031_\"}",
+        "{\"v\":\"ACDC10JSONé00031_\"}",
+    ] {
+        assert!(matches!(
+            Attestation::parse(data.as_bytes()),
+            Err(Error::MissingVersionString)
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+pub fn test_derivation_data() -> Result<(), Error> {
+    let attestation = attestation();
+    let digest = attestation.digest.as_ref().unwrap();
+    let derivation_data = attestation.derivation_data(
+        &digest.derivation.clone().into(),
+        &SerializationFormats::JSON,
+    );
+    assert!(digest.verify_binding(&derivation_data));
+    assert!(!digest.verify_binding(
+        &attestation.derivation_data(&HashFunctionCode::Blake3_256, &SerializationFormats::CBOR)
+    ));
+
+    // Same bytes as encoding of undigested attestation.
+    let undigested = Attestation {
+        digest: None,
+        ..attestation.clone()
+    };
+    for format in [SerializationFormats::JSON, SerializationFormats::CBOR] {
+        assert_eq!(
+            attestation.derivation_data(&HashFunctionCode::Blake3_256, &format),
+            undigested.canonical_bytes(&HashFunctionCode::Blake3_256, &format)?
+        );
+    }
+    Ok(())
+}